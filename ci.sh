@@ -34,5 +34,11 @@ fi
 cargo clippy --all-targets ${VERBOSE} --no-default-features
 cargo clippy --all-targets ${VERBOSE} --all-features
 
+# The runtime (which has no plain `quickcheck` dependency, to test `crate = ...`) gets the same checks
+if [ -d runtime ]
+then
+  (cd runtime && . ../ci.sh)
+fi
+
 cd qcderive-test || exit 0
 . ../ci.sh
@@ -48,19 +48,406 @@ enum EnumManyManyMembers<A, B, C> {
     Third(A, B, C),
 }
 
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+enum EnumNamedMembers {
+    Empty {},
+    Pair { number: u8, unit: () },
+}
+
+#[test]
+fn shrink_variant_one_field_at_a_time() {
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&EnumNamedMembers::Pair {
+        number: 2,
+        unit: (),
+    })
+    .collect();
+    assert_eq!(shrunk.first(), Some(&EnumNamedMembers::Empty {}));
+    assert!(shrunk.len() > 1);
+    assert!(shrunk.iter().skip(1).all(
+        |candidate| matches!(*candidate, EnumNamedMembers::Pair { number, .. } if number < 2)
+    ));
+    assert!(quickcheck::Arbitrary::shrink(&EnumNamedMembers::Empty {})
+        .next()
+        .is_none());
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum CustomGenerator {
+    Named {
+        #[quickcheck(with = odd)]
+        odd: u8,
+    },
+    Unnamed(#[quickcheck(with = odd)] u8),
+}
+
+fn odd(generator: &mut quickcheck::Gen) -> u8 {
+    <u8 as quickcheck::Arbitrary>::arbitrary(generator) | 1
+}
+
+#[test]
+fn custom_generator() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u8..100 {
+        match <CustomGenerator as quickcheck::Arbitrary>::arbitrary(&mut generator) {
+            CustomGenerator::Named { odd } | CustomGenerator::Unnamed(odd) => {
+                assert_eq!(odd & 1, 1);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum CustomShrinker {
+    Fixed(#[quickcheck(no_shrink)] u8),
+    Odd(#[quickcheck(with = odd, shrink_with = shrink_odd)] u8),
+}
+
+fn shrink_odd(odd: &u8) -> Box<dyn Iterator<Item = u8>> {
+    Box::new(quickcheck::Arbitrary::shrink(odd).map(|shrunk| shrunk | 1))
+}
+
+#[test]
+fn custom_shrinker() {
+    assert!(quickcheck::Arbitrary::shrink(&CustomShrinker::Fixed(3))
+        .next()
+        .is_none());
+    assert!(quickcheck::Arbitrary::shrink(&CustomShrinker::Odd(9))
+        .all(|candidate| matches!(candidate, CustomShrinker::Odd(odd) if odd & 1 == 1)));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum Weighted {
+    #[quickcheck(weight = 98)]
+    Data(u8),
+    Error,
+    #[quickcheck(weight = 1)]
+    Timeout,
+}
+
+#[test]
+fn weighted() {
+    let mut generator = quickcheck::Gen::new(100);
+    let data = (0_u16..1000)
+        .filter(|_| {
+            matches!(
+                <Weighted as quickcheck::Arbitrary>::arbitrary(&mut generator),
+                Weighted::Data(_)
+            )
+        })
+        .count();
+    assert!(data > 900, "only {data} of 1000 were `Data`");
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum RareFirst {
+    #[quickcheck(weight = 1)]
+    Rare,
+    #[quickcheck(weight = 99)]
+    Usual,
+}
+
+#[test]
+fn weighted_rare_first() {
+    let mut generator = quickcheck::Gen::new(100);
+    let rare = (0_u16..10000)
+        .filter(|_| {
+            matches!(
+                <RareFirst as quickcheck::Arbitrary>::arbitrary(&mut generator),
+                RareFirst::Rare
+            )
+        })
+        .count();
+    assert!((40..200).contains(&rare), "{rare} of 10000 were `Rare`");
+}
+
+#[test]
+fn weighted_respects_size() {
+    let mut generator = quickcheck::Gen::new(0);
+    for _ in 0_u8..100 {
+        assert!(!matches!(
+            <Weighted as quickcheck::Arbitrary>::arbitrary(&mut generator),
+            Weighted::Data(_)
+        ));
+    }
+}
+
+#[derive(Clone, Debug)]
+struct NotArbitrary;
+
+#[derive(Clone, Debug, QuickCheck)]
+enum Skipped {
+    Generated(u8),
+    #[quickcheck(skip)]
+    Handle(NotArbitrary),
+    Unit,
+}
+
+#[test]
+fn skipped_variants_never_appear() {
+    for size in [0, 1, 100] {
+        let mut generator = quickcheck::Gen::new(size);
+        for _ in 0_u8..100 {
+            assert!(!matches!(
+                <Skipped as quickcheck::Arbitrary>::arbitrary(&mut generator),
+                Skipped::Handle(_)
+            ));
+        }
+    }
+    assert!(
+        quickcheck::Arbitrary::shrink(&Skipped::Handle(NotArbitrary))
+            .next()
+            .is_none()
+    );
+}
+
 #[derive(Clone, Debug, QuickCheck)]
+enum EveryVariantHasFields {
+    One(u8),
+    Two(u16, u16),
+}
+
+#[test]
+fn every_variant_has_fields() {
+    for size in 0..4 {
+        let mut generator = quickcheck::Gen::new(size);
+        for _ in 0_u8..100 {
+            let generated =
+                <EveryVariantHasFields as quickcheck::Arbitrary>::arbitrary(&mut generator);
+            if size == 0 {
+                assert!(matches!(generated, EveryVariantHasFields::One(_)));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum BaseCase {
+    #[quickcheck(base)]
+    Pair(u8, u8),
+    Wrap(Box<BaseCase>),
+}
+
+#[test]
+fn base_case() {
+    let mut generator = quickcheck::Gen::new(0);
+    for _ in 0_u8..100 {
+        assert!(matches!(
+            <BaseCase as quickcheck::Arbitrary>::arbitrary(&mut generator),
+            BaseCase::Pair(..)
+        ));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
 enum LinkedList {
     End,
     More(Box<LinkedList>),
 }
 
-// Doesn't fail but takes a ridiculously long time
-/*
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+enum Expr {
+    Add(Box<Expr>, Box<Expr>),
+    Lit(u8),
+    Neg(Box<Self>),
+}
+
+impl Expr {
+    fn nodes(&self) -> usize {
+        match *self {
+            Self::Add(ref lhs, ref rhs) => {
+                lhs.nodes().saturating_add(rhs.nodes()).saturating_add(1)
+            }
+            Self::Lit(_) => 1,
+            Self::Neg(ref inner) => inner.nodes().saturating_add(1),
+        }
+    }
+}
+
+#[test]
+fn recursion_stays_within_size() {
+    crate::stays_within_size(Expr::nodes, 1);
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum FewestFieldsRecurse {
+    Leaf(u8, u8),
+    Wrap(Box<FewestFieldsRecurse>),
+}
+
+#[test]
+fn recursion_ends_without_base_case() {
+    let mut generator = quickcheck::Gen::new(0);
+    for _ in 0_u8..100 {
+        assert!(matches!(
+            <FewestFieldsRecurse as quickcheck::Arbitrary>::arbitrary(&mut generator),
+            FewestFieldsRecurse::Leaf(..)
+        ));
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+#[quickcheck(split_size)]
+enum SplitSize {
+    Leaf,
+    Node(Vec<u8>, Box<SplitSize>, Box<SplitSize>),
+}
+
+impl SplitSize {
+    fn len(&self) -> usize {
+        match *self {
+            Self::Leaf => 0,
+            Self::Node(ref bytes, ref left, ref right) => [bytes.len(), 1, left.len(), right.len()]
+                .into_iter()
+                .fold(0, usize::saturating_add),
+        }
+    }
+}
+
+#[test]
+fn split_size() {
+    crate::stays_within_size(SplitSize::len, 0);
+}
+
 #[derive(Clone, Debug, QuickCheck)]
 enum Explosion {
     End,
     More(Vec<Explosion>),
+    Capped(#[quickcheck(max_len = 50, each(no_shrink))] Vec<Explosion>),
+}
+
+impl Explosion {
+    fn nodes(&self) -> usize {
+        match *self {
+            Self::End => 1,
+            Self::More(ref children) | Self::Capped(ref children) => children
+                .iter()
+                .map(Self::nodes)
+                .fold(1, usize::saturating_add),
+        }
+    }
 }
-*/
 
-// QuickCheck disallows non-static lifetimes
+#[test]
+fn explosion_stays_within_size() {
+    crate::stays_within_size(Explosion::nodes, 1);
+}
+
+// QuickCheck needs `'static`, so lifetimes become `'static`
+
+#[derive(Clone, Debug, QuickCheck)]
+enum Token<'text> {
+    Number(u8),
+    Word(&'text str),
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+enum Message {
+    Batch(Vec<u8>),
+    Ping,
+    Pong,
+    Reset {
+        #[quickcheck(skip)]
+        reason: String,
+    },
+}
+
+#[test]
+fn shrink_across_variants() {
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&Message::Batch(vec![1, 2])).collect();
+    assert_eq!(shrunk.get(..2), Some(&[Message::Ping, Message::Pong][..]));
+    assert!(shrunk
+        .iter()
+        .skip(2)
+        .all(|candidate| matches!(*candidate, Message::Batch(_))));
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Message::Reset {
+            reason: "closed".to_owned()
+        })
+        .collect::<Vec<_>>(),
+        [Message::Ping, Message::Pong]
+    );
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Message::Pong).collect::<Vec<_>>(),
+        [Message::Ping]
+    );
+    assert!(quickcheck::Arbitrary::shrink(&Message::Ping)
+        .next()
+        .is_none());
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+enum Ordered {
+    Large(u8),
+    #[quickcheck(shrink_order = 2)]
+    Medium,
+    #[quickcheck(shrink_order = 0)]
+    Small,
+}
+
+#[test]
+fn shrink_order() {
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Ordered::Medium).collect::<Vec<_>>(),
+        [Ordered::Small]
+    );
+    assert!(quickcheck::Arbitrary::shrink(&Ordered::Small)
+        .next()
+        .is_none());
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Ordered::Large(0)).collect::<Vec<_>>(),
+        [Ordered::Small, Ordered::Medium]
+    );
+}
+
+#[test]
+fn shrink_to_subterms_first() {
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&Expr::Add(
+        Box::new(Expr::Lit(3)),
+        Box::new(Expr::Neg(Box::new(Expr::Lit(1)))),
+    ))
+    .collect();
+    assert_eq!(
+        shrunk.get(..2),
+        Some(&[Expr::Lit(3), Expr::Neg(Box::new(Expr::Lit(1)))][..])
+    );
+    let list = LinkedList::More(Box::new(LinkedList::More(Box::new(LinkedList::End))));
+    let shrunk_list: Vec<_> = quickcheck::Arbitrary::shrink(&list).collect();
+    assert_eq!(
+        shrunk_list.get(..2),
+        Some(&[LinkedList::End, LinkedList::More(Box::new(LinkedList::End))][..])
+    );
+}
+
+#[derive(Clone, Debug, Default, PartialEq, QuickCheck)]
+enum Level {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+}
+
+#[test]
+fn shrink_toward_default_variant() {
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Level::Warn).collect::<Vec<_>>(),
+        [Level::Info, Level::Debug]
+    );
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&Level::Debug).collect::<Vec<_>>(),
+        [Level::Info]
+    );
+    assert!(quickcheck::Arbitrary::shrink(&Level::Info).next().is_none());
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&EnumManyNoMembers::Third).count(),
+        2
+    );
+    assert!(quickcheck::Arbitrary::shrink(&EnumManyNoMembers::First)
+        .next()
+        .is_none());
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+enum Tree<T> {
+    Leaf(T),
+    Node(Vec<(T, Box<Tree<T>>)>),
+}
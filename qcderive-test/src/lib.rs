@@ -10,8 +10,23 @@
 #![warn(clippy::all, clippy::nursery, clippy::pedantic, clippy::restriction)]
 #![allow(
     clippy::blanket_clippy_restriction_lints,
+    clippy::implicit_return,
     clippy::missing_docs_in_private_items
 )]
 
 mod enums;
 mod structs;
+mod unions;
+
+/// Generate values at every size up to 200 and check that `measure` never exceeds the size,
+/// or `at_least` if a value can't get any smaller than that (a tree always has its root, say).
+#[cfg(test)]
+fn stays_within_size<T: quickcheck::Arbitrary>(measure: fn(&T) -> usize, at_least: usize) {
+    for size in 0..200 {
+        let mut generator = quickcheck::Gen::new(size);
+        for _ in 0_u8..10 {
+            let measured = measure(&T::arbitrary(&mut generator));
+            assert!(measured <= size.max(at_least), "{measured} at size {size}");
+        }
+    }
+}
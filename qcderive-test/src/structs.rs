@@ -42,13 +42,747 @@ struct TupleStructMany<A, B, C>(A, B, C);
 #[derive(Clone, Debug, QuickCheck)]
 struct WithConstParam<T, const N: usize>(T, T, T);
 
-#[derive(Clone, Debug, QuickCheck)]
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct Settings {
+    name: String,
+    port: u16,
+    retries: u8,
+    verbose: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct Packet(u32, Vec<u8>);
+
+#[test]
+fn shrink_structs() {
+    let settings = Settings {
+        name: "localhost".to_owned(),
+        port: 8080,
+        retries: 7,
+        verbose: true,
+    };
+    let smaller_settings: Vec<_> = quickcheck::Arbitrary::shrink(&settings).collect();
+    assert!(!smaller_settings.is_empty());
+    assert!(smaller_settings
+        .iter()
+        .all(|candidate| *candidate != settings
+            && candidate.port <= 8080
+            && candidate.retries <= 7));
+    let packet = Packet(12345, vec![1, 7, 3]);
+    let smaller_packets: Vec<_> = quickcheck::Arbitrary::shrink(&packet).collect();
+    assert!(!smaller_packets.is_empty());
+    assert!(smaller_packets
+        .iter()
+        .all(|candidate| *candidate != packet && candidate.0 <= 12345));
+    assert!(quickcheck::Arbitrary::shrink(&StructNoneSemicolon)
+        .next()
+        .is_none());
+    assert!(quickcheck::Arbitrary::shrink(&BraceStructEmpty {})
+        .next()
+        .is_none());
+    assert!(quickcheck::Arbitrary::shrink(&TupleStructEmpty())
+        .next()
+        .is_none());
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
 struct LinkedList(Option<Box<LinkedList>>);
 
-// Doesn't fail but takes a ridiculously long time
-/*
+impl LinkedList {
+    fn len(&self) -> usize {
+        self.0
+            .as_ref()
+            .map_or(0, |next| next.len().saturating_add(1))
+    }
+}
+
+#[test]
+fn linked_list_stays_within_size() {
+    crate::stays_within_size(LinkedList::len, 0);
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct AlreadyMinimalFields {
+    flag: bool,
+    number: u8,
+    unit: (),
+}
+
+#[test]
+fn shrink_one_field_at_a_time() {
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&AlreadyMinimalFields {
+        flag: false,
+        number: 2,
+        unit: (),
+    })
+    .collect();
+    assert!(!shrunk.is_empty());
+    assert!(shrunk
+        .iter()
+        .all(|candidate| !candidate.flag && candidate.number < 2));
+}
+
 #[derive(Clone, Debug, QuickCheck)]
+struct CustomGenerator {
+    #[quickcheck(with = even)]
+    even: u8,
+    other: u8,
+}
+
+fn even(generator: &mut quickcheck::Gen) -> u8 {
+    <u8 as quickcheck::Arbitrary>::arbitrary(generator) & !1
+}
+
+#[test]
+fn custom_generator() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u8..100 {
+        let generated = <CustomGenerator as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        assert_eq!(generated.even & 1, 0);
+    }
+}
+
+/// Shrink `value` the way `quickcheck` does: keep the first candidate that still fails until none does.
+fn minimize<T: quickcheck::Arbitrary>(mut value: T, fails: impl Fn(&T) -> bool) -> T {
+    while let Some(smaller) = quickcheck::Arbitrary::shrink(&value).find(&fails) {
+        value = smaller;
+    }
+    value
+}
+
+#[test]
+fn shrink_structs_to_minimal_counterexample() {
+    let settings = Settings {
+        name: "localhost".to_owned(),
+        port: 8080,
+        retries: 7,
+        verbose: true,
+    };
+    assert_eq!(
+        minimize(settings, |candidate| candidate.port >= 1000),
+        Settings {
+            name: String::new(),
+            port: 1000,
+            retries: 0,
+            verbose: false,
+        },
+    );
+    assert_eq!(
+        minimize(Packet(12345, vec![1, 7, 3]), |candidate| candidate
+            .1
+            .contains(&7)),
+        Packet(0, vec![7]),
+    );
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct CustomShrinker {
+    #[quickcheck(no_shrink)]
+    fixed: u8,
+    #[quickcheck(with = even, shrink_with = shrink_even)]
+    even: u8,
+}
+
+fn shrink_even(even: &u8) -> Box<dyn Iterator<Item = u8>> {
+    Box::new(quickcheck::Arbitrary::shrink(even).map(|shrunk| shrunk & !1))
+}
+
+#[test]
+fn custom_shrinker() {
+    let shrunk: Vec<_> =
+        quickcheck::Arbitrary::shrink(&CustomShrinker { even: 8, fixed: 3 }).collect();
+    assert!(!shrunk.is_empty());
+    assert!(shrunk
+        .iter()
+        .all(|candidate| candidate.fixed == 3 && candidate.even & 1 == 0));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Ranges {
+    #[quickcheck(finite)]
+    finite: f64,
+    #[quickcheck(range = 0.0..1.0, finite)]
+    fraction: f32,
+    #[quickcheck(range = -5..5)]
+    negative: i8,
+    #[quickcheck(range = 1..=65535)]
+    port: u16,
+    #[quickcheck(range = 100..)]
+    unbounded: u64,
+}
+
+#[test]
+fn ranges() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u16..1000 {
+        let generated = <Ranges as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        assert!(generated.finite.is_finite());
+        assert!((0.0..1.0).contains(&generated.fraction));
+        assert!((-5..5).contains(&generated.negative));
+        assert_ne!(generated.port, 0);
+        assert!(generated.unbounded >= 100);
+        for shrunk in quickcheck::Arbitrary::shrink(&generated) {
+            assert!((0.0..1.0).contains(&shrunk.fraction));
+            assert!((-5..5).contains(&shrunk.negative));
+            assert_ne!(shrunk.port, 0);
+            assert!(shrunk.unbounded >= 100);
+        }
+    }
+}
+
+#[test]
+fn ranges_shrink_toward_lower_bound() {
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&Ranges {
+        finite: 0.0,
+        fraction: 0.5,
+        negative: 4,
+        port: 8080,
+        unbounded: 100,
+    })
+    .collect();
+    assert!(shrunk.iter().any(|candidate| candidate.negative == -5));
+    assert!(shrunk.iter().any(|candidate| candidate.port == 1));
+    assert!(shrunk.iter().any(|candidate| candidate.fraction == 0.0));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct FullWidth {
+    #[quickcheck(range = f64::MIN..=f64::MAX)]
+    double: f64,
+    #[quickcheck(range = f32::MIN..f32::MAX)]
+    single: f32,
+}
+
+#[test]
+fn full_width_ranges() {
+    let mut generator = quickcheck::Gen::new(100);
+    let all: Vec<_> =
+        core::iter::repeat_with(|| <FullWidth as quickcheck::Arbitrary>::arbitrary(&mut generator))
+            .take(1000)
+            .collect();
+    let positive_doubles = all
+        .iter()
+        .filter(|generated| generated.double.is_sign_positive())
+        .count();
+    let positive_singles = all
+        .iter()
+        .filter(|generated| generated.single.is_sign_positive())
+        .count();
+    assert!(
+        (300..700).contains(&positive_doubles),
+        "{positive_doubles} positive"
+    );
+    assert!(
+        (300..700).contains(&positive_singles),
+        "{positive_singles} positive"
+    );
+    for generated in &all {
+        for shrunk in quickcheck::Arbitrary::shrink(generated).take(100) {
+            assert!(shrunk.double.is_finite() && shrunk.single.is_finite());
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Lengths {
+    #[quickcheck(len = 3)]
+    exact: Vec<u8>,
+    #[quickcheck(len = 1..=8)]
+    map: std::collections::HashMap<u8, bool>,
+    #[quickcheck(max_len = 4)]
+    max: std::collections::VecDeque<()>,
+    #[quickcheck(min_len = 2)]
+    min: std::collections::BTreeSet<u8>,
+    #[quickcheck(non_empty)]
+    string: String,
+}
+
+#[test]
+fn lengths() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u16..1000 {
+        let generated = <Lengths as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        assert_eq!(generated.exact.len(), 3);
+        assert!((1..=8).contains(&generated.map.len()));
+        assert!(generated.max.len() <= 4);
+        assert!(generated.min.len() >= 2);
+        assert!(!generated.string.is_empty());
+    }
+}
+
+#[test]
+fn lengths_survive_shrinking() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u8..100 {
+        let generated = <Lengths as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        for shrunk in quickcheck::Arbitrary::shrink(&generated) {
+            assert_eq!(shrunk.exact.len(), 3);
+            assert!(!shrunk.map.is_empty());
+            assert!(shrunk.min.len() >= 2);
+            assert!(!shrunk.string.is_empty());
+        }
+    }
+}
+
+// Only `too_few_distinct_elements` runs out of distinct values, so the derived property test still passes
+thread_local! {
+    static ONLY_TWO_VALUES: core::cell::Cell<bool> = const { core::cell::Cell::new(false) };
+}
+
+fn maybe_two_values(generator: &mut quickcheck::Gen) -> u8 {
+    let byte = <u8 as quickcheck::Arbitrary>::arbitrary(generator);
+    if ONLY_TWO_VALUES.get() {
+        byte & 1
+    } else {
+        byte
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Distinct {
+    #[quickcheck(min_len = 3, each(with = maybe_two_values))]
+    set: std::collections::BTreeSet<u8>,
+}
+
+#[test]
+#[should_panic(expected = "couldn't generate 3 distinct elements")]
+fn too_few_distinct_elements() {
+    ONLY_TWO_VALUES.set(true);
+    <Distinct as quickcheck::Arbitrary>::arbitrary(&mut quickcheck::Gen::new(10));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Elements {
+    #[quickcheck(keys(range = 1..=9), values(non_empty))]
+    map: std::collections::BTreeMap<u8, Vec<bool>>,
+    #[quickcheck(each(range = 1..=9))]
+    maybe: Option<u8>,
+    #[quickcheck(each(range = 1..=65535), min_len = 2)]
+    ports: Vec<u16>,
+    #[quickcheck(each(with = even, shrink_with = shrink_even))]
+    set: std::collections::HashSet<u8>,
+}
+
+#[test]
+fn elements() {
+    let mut generator = quickcheck::Gen::new(30);
+    for _ in 0_u8..100 {
+        let generated = <Elements as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        for shrunk in quickcheck::Arbitrary::shrink(&generated).chain([generated]) {
+            assert!(shrunk
+                .map
+                .iter()
+                .all(|(key, value)| (1..=9).contains(key) && !value.is_empty()));
+            assert!(shrunk.maybe.map_or(true, |maybe| (1..=9).contains(&maybe)));
+            assert!(shrunk.ports.len() >= 2);
+            assert!(shrunk.ports.iter().all(|port| *port != 0));
+            assert!(shrunk.set.iter().all(|even| even & 1 == 0));
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Patterns {
+    #[quickcheck(regex = "[a-z][a-z0-9_]{0,15}")]
+    ident: String,
+    #[quickcheck(each(regex = "(a|bc)+"))]
+    tokens: Vec<String>,
+    #[quickcheck(regex = r"(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)")]
+    version: String,
+}
+
+fn is_ident(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    chars.next().is_some_and(|first| first.is_ascii_lowercase())
+        && ident.len() <= 16
+        && chars.all(|rest| rest.is_ascii_lowercase() || rest.is_ascii_digit() || rest == '_')
+}
+
+fn is_token(token: &str) -> bool {
+    !token.is_empty() && token.replace("bc", "").chars().all(|rest| rest == 'a')
+}
+
+fn is_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part.chars().all(|digit| digit.is_ascii_digit())
+                && (*part == "0" || !part.starts_with('0'))
+        })
+}
+
+#[test]
+fn patterns() {
+    let mut generator = quickcheck::Gen::new(10);
+    for _ in 0_u8..100 {
+        let generated = <Patterns as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        for shrunk in quickcheck::Arbitrary::shrink(&generated).chain([generated]) {
+            assert!(is_ident(&shrunk.ident), "{:?}", shrunk.ident);
+            assert!(
+                shrunk.tokens.iter().all(|token| is_token(token)),
+                "{:?}",
+                shrunk.tokens
+            );
+            assert!(is_version(&shrunk.version), "{:?}", shrunk.version);
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Picks {
+    #[quickcheck(regex = "x|y|z")]
+    choice: String,
+    #[quickcheck(regex = "[a-z]")]
+    letter: String,
+}
+
+#[test]
+fn patterns_pick_uniformly() {
+    let mut generator = quickcheck::Gen::new(10);
+    let picks: Vec<Picks> =
+        core::iter::repeat_with(|| <Picks as quickcheck::Arbitrary>::arbitrary(&mut generator))
+            .take(6000)
+            .collect();
+    let xs = picks.iter().filter(|picked| picked.choice == "x").count();
+    assert!((1880..2120).contains(&xs), "{xs} of 6000 were `x`");
+    let as_ = picks.iter().filter(|picked| picked.letter == "a").count();
+    assert!((150..320).contains(&as_), "{as_} of 6000 were `a`");
+}
+
+#[test]
+fn patterns_shrink_to_shortest_match() {
+    let mut generator = quickcheck::Gen::new(30);
+    let mut patterns = <Patterns as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    while let Some(shrunk) = quickcheck::Arbitrary::shrink(&patterns).next() {
+        patterns = shrunk;
+    }
+    assert_eq!(patterns.ident, "a");
+    assert!(patterns.tokens.is_empty());
+    assert_eq!(patterns.version, "0.0.0");
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct NotArbitrary(u8);
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Skipped {
+    number: u8,
+    #[quickcheck(skip)]
+    handle: NotArbitrary,
+}
+
+#[test]
+fn skipped_fields_use_default() {
+    let mut generator = quickcheck::Gen::new(100);
+    for _ in 0_u8..100 {
+        let generated = <Skipped as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        for shrunk in quickcheck::Arbitrary::shrink(&generated).chain([generated]) {
+            assert_eq!(shrunk.handle, NotArbitrary(0));
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct Reproducible {
+    list: Vec<u8>,
+    nested: Nested,
+    pair: (u64, String),
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct Nested(u32, Option<char>, Vec<i16>);
+
+#[test]
+fn reproducible_from_seed() {
+    for seed in 0..100 {
+        let mut first = quickcheck::Gen::from_size_and_seed(50, seed);
+        let mut second = quickcheck::Gen::from_size_and_seed(50, seed);
+        assert_eq!(
+            <Reproducible as quickcheck::Arbitrary>::arbitrary(&mut first),
+            <Reproducible as quickcheck::Arbitrary>::arbitrary(&mut second),
+        );
+    }
+}
+
+/// Sibling fields with the same size only come out equal if their `Gen`s got the same seed
+/// (or, for very short ones, if `u64::arbitrary` happened to pick the same edge cases).
+#[derive(Clone, Debug, QuickCheck)]
+struct Siblings(
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+    #[quickcheck(size = 20)] Vec<u64>,
+);
+
+#[test]
+fn siblings_get_different_seeds() {
+    let mut generator = quickcheck::Gen::new(10);
+    for _ in 0_u16..20000 {
+        let siblings = <Siblings as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        let all = [
+            &siblings.0,
+            &siblings.1,
+            &siblings.2,
+            &siblings.3,
+            &siblings.4,
+            &siblings.5,
+            &siblings.6,
+            &siblings.7,
+        ];
+        for (i, first) in all.iter().enumerate() {
+            for second in all.iter().skip(i + 1) {
+                assert!(first.len() < 4 || first != second, "{siblings:?}");
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+#[quickcheck(split_size)]
+struct Wide {
+    bytes: Vec<u8>,
+    #[quickcheck(max_len = 500)]
+    capped: Vec<u8>,
+    #[quickcheck(each(each(range = 1..=9)))]
+    digits: Vec<Vec<u8>>,
+    name: String,
+    nested: Vec<Vec<u8>>,
+    next: Option<Box<Wide>>,
+    words: Vec<u16>,
+}
+
+impl Wide {
+    fn len(&self) -> usize {
+        [
+            self.bytes.len(),
+            self.capped.len(),
+            self.digits.len(),
+            self.digits.iter().map(Vec::len).sum(),
+            self.name.chars().count(),
+            self.nested.len(),
+            self.nested.iter().map(Vec::len).sum(),
+            self.next
+                .as_ref()
+                .map_or(0, |next| next.len().saturating_add(1)),
+            self.words.len(),
+        ]
+        .into_iter()
+        .fold(0, usize::saturating_add)
+    }
+}
+
+#[test]
+fn split_size() {
+    crate::stays_within_size(Wide::len, 0);
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
 struct Explosion(Vec<Explosion>);
-*/
 
-// QuickCheck disallows non-static lifetimes
+impl Explosion {
+    fn nodes(&self) -> usize {
+        self.0
+            .iter()
+            .map(Self::nodes)
+            .fold(1, usize::saturating_add)
+    }
+}
+
+#[test]
+fn explosion_stays_within_size() {
+    crate::stays_within_size(Explosion::nodes, 1);
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+struct Rose(#[quickcheck(max_len = 3)] Vec<Rose>);
+
+impl Rose {
+    fn nodes(&self) -> usize {
+        self.0
+            .iter()
+            .map(Self::nodes)
+            .fold(1, usize::saturating_add)
+    }
+}
+
+#[test]
+fn bounded_recursive_collection_ends() {
+    let mut generator = quickcheck::Gen::new(0);
+    for _ in 0_u8..100 {
+        assert_eq!(
+            <Rose as quickcheck::Arbitrary>::arbitrary(&mut generator),
+            Rose(vec![])
+        );
+    }
+    crate::stays_within_size(Rose::nodes, 1);
+}
+
+// QuickCheck needs `'static`, so lifetimes become `'static`
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Borrowed<'name, 'label: 'name, T> {
+    label: std::borrow::Cow<'label, str>,
+    name: &'name str,
+    value: T,
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Text<'text> {
+    label: std::borrow::Cow<'text, str>,
+    name: &'text str,
+}
+
+#[test]
+fn lifetimes_become_static() {
+    let mut generator = quickcheck::Gen::new(10);
+    let text: Text<'static> = quickcheck::Arbitrary::arbitrary(&mut generator);
+    assert!(quickcheck::Arbitrary::shrink(&text).all(|shrunk| {
+        shrunk.name.chars().count() <= text.name.chars().count()
+            && shrunk.label.chars().count() <= text.label.chars().count()
+    }));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct SizedFields {
+    #[quickcheck(size = 3)]
+    header: Vec<u8>,
+    #[quickcheck(size_scale = 0.25)]
+    payload: Vec<u8>,
+    rest: Vec<u8>,
+}
+
+#[test]
+fn sized_fields() {
+    let mut generator = quickcheck::Gen::new(100);
+    let mut longest_rest = 0;
+    for _ in 0_u8..100 {
+        let sized = <SizedFields as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        assert!(sized.header.len() <= 3, "header {:?}", sized.header);
+        assert!(sized.payload.len() <= 25, "payload {:?}", sized.payload);
+        longest_rest = longest_rest.max(sized.rest.len());
+    }
+    assert!(longest_rest > 25, "other fields should keep the full size");
+}
+
+#[test]
+fn shrink_to_subterms_first() {
+    let list = LinkedList(Some(Box::new(LinkedList(None))));
+    assert_eq!(
+        quickcheck::Arbitrary::shrink(&list).next(),
+        Some(LinkedList(None))
+    );
+    let tree = Explosion(vec![Explosion(vec![]), Explosion(vec![Explosion(vec![])])]);
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&tree).collect();
+    assert_eq!(
+        shrunk.get(..2),
+        Some(&[Explosion(vec![]), Explosion(vec![Explosion(vec![])])][..])
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, QuickCheck)]
+#[quickcheck(shrink_to_default)]
+struct Config {
+    retries: u8,
+    verbose: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            verbose: false,
+        }
+    }
+}
+
+#[test]
+fn shrink_to_default() {
+    let config = Config {
+        retries: 0,
+        verbose: true,
+    };
+    let shrunk: Vec<_> = quickcheck::Arbitrary::shrink(&config).collect();
+    assert_eq!(shrunk.first(), Some(&Config::default()));
+    assert!(shrunk.len() > 1);
+    assert!(quickcheck::Arbitrary::shrink(&Config::default())
+        .next()
+        .is_none());
+}
+
+#[derive(Clone, Debug)]
+struct User;
+
+#[derive(Clone, Debug, QuickCheck)]
+#[quickcheck(bound = "T: Clone + 'static")]
+struct Handle<T> {
+    id: u64,
+    #[quickcheck(skip)]
+    marker: core::marker::PhantomData<T>,
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Tagged<#[quickcheck(bound = "")] T: Clone + 'static, U> {
+    #[quickcheck(skip)]
+    tag: core::marker::PhantomData<T>,
+    value: U,
+}
+
+#[test]
+fn bound_overrides() {
+    let mut generator = quickcheck::Gen::new(10);
+    let handle = <Handle<User> as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    assert!(quickcheck::Arbitrary::shrink(&handle).all(|shrunk| shrunk.id < handle.id));
+    let tagged = <Tagged<User, u8> as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    assert!(quickcheck::Arbitrary::shrink(&tagged).all(|shrunk| shrunk.value < tagged.value));
+}
+
+trait Shape {
+    type Output;
+}
+
+#[derive(Clone, Debug)]
+struct Circle;
+
+impl Shape for Circle {
+    type Output = u8;
+}
+
+impl Shape for () {
+    type Output = bool;
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Measured<T: Shape> {
+    outputs: Vec<T::Output>,
+    #[quickcheck(skip)]
+    shape: core::marker::PhantomData<T>,
+    total: <T as Shape>::Output,
+}
+
+#[test]
+fn bounds_from_field_types() {
+    let mut generator = quickcheck::Gen::new(10);
+    let measured = <Measured<Circle> as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    assert!(quickcheck::Arbitrary::shrink(&measured).all(|shrunk| {
+        shrunk.total < measured.total || shrunk.outputs.len() <= measured.outputs.len()
+    }));
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+struct Id<T> {
+    marker: core::marker::PhantomData<T>,
+    pinned: core::marker::PhantomPinned,
+    raw: u64,
+}
+
+#[test]
+fn markers_need_no_bounds() {
+    let mut generator = quickcheck::Gen::new(10);
+    let id = <Id<User> as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    assert!(quickcheck::Arbitrary::shrink(&id).all(|shrunk| shrunk.raw < id.raw));
+}
@@ -0,0 +1,114 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Testing ways to write a `union`.
+
+#![allow(clippy::tests_outside_test_module, dead_code)]
+
+use derive_quickcheck::QuickCheck;
+
+// Reading a `union` field is `unsafe`, so these fields log when they're initialized instead
+thread_local! {
+    static INITIALIZED: core::cell::RefCell<Vec<&'static str>> = const { core::cell::RefCell::new(vec![]) };
+}
+
+fn initialized() -> Vec<&'static str> {
+    INITIALIZED.with_borrow_mut(core::mem::take)
+}
+
+fn int(generator: &mut quickcheck::Gen) -> u32 {
+    INITIALIZED.with_borrow_mut(|log| log.push("int"));
+    <u32 as quickcheck::Arbitrary>::arbitrary(generator)
+}
+
+fn float(generator: &mut quickcheck::Gen) -> f32 {
+    INITIALIZED.with_borrow_mut(|log| log.push("float"));
+    <f32 as quickcheck::Arbitrary>::arbitrary(generator)
+}
+
+#[derive(Clone, Copy, QuickCheck)]
+#[repr(C)]
+union Active {
+    #[quickcheck(active, with = int)]
+    int: u32,
+    float: f32,
+}
+
+impl core::fmt::Debug for Active {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Active")
+    }
+}
+
+#[test]
+fn active() {
+    let mut generator = quickcheck::Gen::new(10);
+    initialized();
+    for _ in 0_u8..100 {
+        let value = <Active as quickcheck::Arbitrary>::arbitrary(&mut generator);
+        assert!(quickcheck::Arbitrary::shrink(&value).next().is_none());
+    }
+    assert_eq!(initialized(), vec!["int"; 100]);
+}
+
+#[derive(Clone, Copy, QuickCheck)]
+#[repr(C)]
+union Weighted {
+    #[quickcheck(weight = 9, with = int)]
+    int: u32,
+    #[quickcheck(weight = 1, with = float)]
+    float: f32,
+}
+
+impl core::fmt::Debug for Weighted {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Weighted")
+    }
+}
+
+#[test]
+fn weighted() {
+    let mut generator = quickcheck::Gen::new(10);
+    initialized();
+    for _ in 0_u16..1000 {
+        <Weighted as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    }
+    let log = initialized();
+    let ints = log.iter().filter(|&&field| field == "int").count();
+    assert_eq!(log.len(), 1000);
+    assert!((800..1000).contains(&ints), "{ints} of 1000 were `int`");
+}
+
+#[derive(Clone, Copy, QuickCheck)]
+#[repr(C)]
+union LightFirst {
+    #[quickcheck(weight = 1, with = float)]
+    float: f32,
+    #[quickcheck(weight = 99, with = int)]
+    int: u32,
+}
+
+impl core::fmt::Debug for LightFirst {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("LightFirst")
+    }
+}
+
+#[test]
+fn weighted_light_first() {
+    let mut generator = quickcheck::Gen::new(10);
+    initialized();
+    for _ in 0_u16..10000 {
+        <LightFirst as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    }
+    let log = initialized();
+    let floats = log.iter().filter(|&&field| field == "float").count();
+    assert_eq!(log.len(), 10000);
+    assert!(
+        (40..200).contains(&floats),
+        "{floats} of 10000 were `float`"
+    );
+}
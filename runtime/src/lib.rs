@@ -0,0 +1,25 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! `#[derive(QuickCheck)]` and the `quickcheck` it needs, re-exported together,
+//! so a crate (or a facade over several) can depend on this alone.
+//! Point the derive here with `#[quickcheck(crate = derive_quickcheck_runtime::quickcheck)]`.
+
+#![no_std]
+#![deny(warnings)]
+#![warn(
+    clippy::all,
+    clippy::nursery,
+    clippy::pedantic,
+    clippy::restriction,
+    clippy::cargo,
+    missing_docs,
+    rustdoc::all
+)]
+#![allow(clippy::blanket_clippy_restriction_lints, clippy::pub_use)]
+
+pub use derive_quickcheck::QuickCheck;
+pub use qc as quickcheck;
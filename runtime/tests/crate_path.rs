@@ -0,0 +1,56 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Deriving through `#[quickcheck(crate = ...)]` where `::quickcheck` itself doesn't resolve.
+
+#![allow(clippy::tests_outside_test_module, dead_code)]
+
+use derive_quickcheck_runtime::{quickcheck, QuickCheck};
+
+/// Stands in for a crate that wraps `quickcheck` for the rest of a workspace.
+mod facade {
+    pub(super) use derive_quickcheck_runtime::quickcheck;
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+#[quickcheck(crate = derive_quickcheck_runtime::quickcheck)]
+struct Point<T> {
+    #[quickcheck(range = 1..10)]
+    count: u8,
+    #[quickcheck(regex = "[a-z]+")]
+    name: String,
+    values: Vec<T>,
+}
+
+#[derive(Clone, Debug, QuickCheck)]
+#[quickcheck(crate = facade::quickcheck)]
+enum Shape {
+    Circle(#[quickcheck(range = 0.0..1.0)] f64),
+    #[quickcheck(weight = 2)]
+    Polygon(#[quickcheck(non_empty)] Vec<(i8, i8)>),
+    Nothing,
+}
+
+#[test]
+fn through_runtime() {
+    let mut generator = quickcheck::Gen::new(10);
+    let point = <Point<u8> as quickcheck::Arbitrary>::arbitrary(&mut generator);
+    assert!((1..10).contains(&point.count));
+    assert!(!point.name.is_empty());
+    assert!(quickcheck::Arbitrary::shrink(&point).all(|shrunk| (1..10).contains(&shrunk.count)));
+}
+
+#[test]
+fn through_facade() {
+    let mut generator = facade::quickcheck::Gen::new(10);
+    for _ in 0_u8..100 {
+        if let Shape::Polygon(points) =
+            <Shape as facade::quickcheck::Arbitrary>::arbitrary(&mut generator)
+        {
+            assert!(!points.is_empty());
+        }
+    }
+}
@@ -0,0 +1,403 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Parse `#[quickcheck(...)]` helper attributes.
+
+use quote::quote;
+use syn::spanned::Spanned;
+
+/// Everything `#[quickcheck(...)]` can say about a single field.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default)]
+pub struct Field {
+    /// `#[quickcheck(each(...))]`: apply these attributes to each element of a collection (or to the inside of an `Option`).
+    pub each: Option<Box<Self>>,
+    /// `#[quickcheck(finite)]`: never generate `NaN` or infinities.
+    pub finite: bool,
+    /// `#[quickcheck(keys(...))]`: apply these attributes to each key of a map.
+    pub keys: Option<Box<Self>>,
+    /// `#[quickcheck(len = lo..hi)]` or `#[quickcheck(len = n)]`: number of elements in a collection.
+    pub len: Option<syn::Expr>,
+    /// `#[quickcheck(max_len = n)]`: at most this many elements in a collection.
+    pub max_len: Option<syn::Expr>,
+    /// `#[quickcheck(min_len = n)]`: at least this many elements in a collection, even while shrinking.
+    pub min_len: Option<syn::Expr>,
+    /// `#[quickcheck(no_shrink)]`: hold this field fixed while shrinking the rest.
+    pub no_shrink: bool,
+    /// `#[quickcheck(non_empty)]`: at least one element in a collection, even while shrinking.
+    pub non_empty: bool,
+    /// `#[quickcheck(range = lo..hi)]`: generate numbers in this range and shrink toward `lo`.
+    pub range: Option<syn::ExprRange>,
+    /// `#[quickcheck(regex = "...")]`: generate strings that match this pattern from start to end.
+    pub regex: Option<syn::LitStr>,
+    /// `#[quickcheck(shrink_with = path)]`: call `path(&field)` instead of `Arbitrary::shrink`.
+    pub shrink_with: Option<syn::Path>,
+    /// `#[quickcheck(size = n)]`: generate this field with a `Gen` of exactly this size.
+    pub size: Option<syn::Expr>,
+    /// `#[quickcheck(size_scale = x)]`: generate this field with a `Gen` this many times the parent's size.
+    pub size_scale: Option<syn::Expr>,
+    /// `#[quickcheck(skip)]`: always `Default::default()`, never shrunk, so the type needn't implement `Arbitrary`.
+    pub skip: bool,
+    /// `#[quickcheck(values(...))]`: apply these attributes to each value of a map.
+    pub values: Option<Box<Self>>,
+    /// `#[quickcheck(with = path)]`: call `path(&mut g)` instead of `Arbitrary::arbitrary`.
+    pub with: Option<syn::Path>,
+}
+
+impl Field {
+    /// Reject combinations of attributes that contradict each other.
+    fn check(&self, span: proc_macro2::Span) -> syn::Result<()> {
+        if self.skip
+            && (self.with.is_some()
+                || self.shrink_with.is_some()
+                || self.no_shrink
+                || self.range.is_some()
+                || self.regex.is_some()
+                || self.finite
+                || self.has_len()
+                || self.has_elements()
+                || self.has_size())
+        {
+            return Err(syn::Error::new(
+                span,
+                "`skip` always uses `Default::default()`, so it can't take any other attributes",
+            ));
+        }
+        if self.no_shrink && self.shrink_with.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "`no_shrink` and `shrink_with` can't both apply to one field",
+            ));
+        }
+        if self.with.is_some()
+            && (self.range.is_some() || self.finite || self.has_len() || self.has_elements())
+        {
+            return Err(syn::Error::new(
+                span,
+                "`with` already decides how to generate this field, so it can't also take `range`, `finite`, a length, or element attributes",
+            ));
+        }
+        if self.regex.is_some()
+            && (self.with.is_some()
+                || self.range.is_some()
+                || self.finite
+                || self.has_len()
+                || self.has_elements())
+        {
+            return Err(syn::Error::new(
+                span,
+                "`regex` already decides which strings to generate, so it can't also take `with`, `range`, `finite`, a length, or element attributes",
+            ));
+        }
+        if self.size.is_some() && self.size_scale.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "`size` and `size_scale` can't both apply to one field",
+            ));
+        }
+        if self.len.is_some() && (self.min_len.is_some() || self.max_len.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "`len` already bounds the length, so it can't also take `min_len` or `max_len`",
+            ));
+        }
+        if self.range.is_some() && (self.has_len() || self.has_elements()) {
+            return Err(syn::Error::new(
+                span,
+                "`range` is for numbers, and lengths and element attributes are for collections, so one field can't take both",
+            ));
+        }
+        if self.each.is_some() && (self.keys.is_some() || self.values.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "`each` already covers every element, so it can't also take `keys` or `values`",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether any attributes apply to elements rather than the whole field.
+    pub const fn has_elements(&self) -> bool {
+        self.each.is_some() || self.keys.is_some() || self.values.is_some()
+    }
+
+    /// Whether any length constraint applies.
+    pub const fn has_len(&self) -> bool {
+        self.len.is_some() || self.min_len.is_some() || self.max_len.is_some() || self.non_empty
+    }
+
+    /// Whether this field asks for a `Gen` of its own size.
+    pub const fn has_size(&self) -> bool {
+        self.size.is_some() || self.size_scale.is_some()
+    }
+}
+
+/// Everything `#[quickcheck(...)]` can say about the whole `struct` or `enum`.
+#[derive(Default)]
+pub struct Container {
+    /// `#[quickcheck(bound = "T: Clone, ...")]`: require exactly these, instead of `Arbitrary` on every type parameter.
+    pub bound: Option<Vec<syn::WherePredicate>>,
+    /// `#[quickcheck(crate = path)]`: refer to `quickcheck` through this path instead of `::quickcheck`.
+    pub krate: Option<syn::Path>,
+    /// `#[quickcheck(shrink_to_default)]`: try `Default::default()` before any other shrink, and don't shrink it any further.
+    pub shrink_to_default: bool,
+    /// `#[quickcheck(split_size)]`: divide the size randomly among fields (and collections' elements) instead of giving each nearly all of it.
+    pub split_size: bool,
+}
+
+/// Everything `#[quickcheck(...)]` can say about a single enum variant.
+#[derive(Default)]
+pub struct Variant {
+    /// `#[quickcheck(base)]`: a base case to fall back on when no variant is small enough for the current size.
+    pub base: bool,
+    /// `#[quickcheck(shrink_order = n)]`: shrink toward this variant before variants with a larger `n` (by default, its position).
+    pub shrink_order: Option<usize>,
+    /// `#[quickcheck(skip)]`: never generate this variant, so its fields needn't implement `Arbitrary`.
+    pub skip: bool,
+    /// `#[quickcheck(weight = n)]`: choose this variant `n` times as often as a variant with weight 1 (the default).
+    pub weight: Option<u32>,
+}
+
+/// Everything `#[quickcheck(...)]` can say about which field of a `union` to initialize.
+#[derive(Default)]
+pub struct Member {
+    /// `#[quickcheck(active)]`: always initialize this field.
+    pub active: bool,
+    /// `#[quickcheck(weight = n)]`: initialize this field `n` times as often as a field with weight 1.
+    pub weight: Option<u32>,
+}
+
+/// Everything `#[quickcheck(...)]` can say about a type parameter.
+#[derive(Default)]
+pub struct Param {
+    /// `#[quickcheck(bound = "T: Clone, ...")]`: require these instead of `T: Arbitrary` (so `bound = ""` requires nothing).
+    pub bound: Option<Vec<syn::WherePredicate>>,
+}
+
+impl Variant {
+    /// Relative likelihood of choosing this variant.
+    pub fn weight(&self) -> u32 {
+        self.weight.unwrap_or(1)
+    }
+}
+
+/// Set an option exactly once, or complain about the duplicate.
+fn set_once<T>(
+    slot: &mut Option<T>,
+    meta: &syn::meta::ParseNestedMeta<'_>,
+    value: T,
+) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(meta.error("duplicate `quickcheck` attribute"));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Set a flag exactly once, or complain about the duplicate.
+fn set_flag(flag: &mut bool, meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+    if *flag {
+        return Err(meta.error("duplicate `quickcheck` attribute"));
+    }
+    *flag = true;
+    Ok(())
+}
+
+/// Parse a `weight = n`, which can't be 0.
+fn weight(lit: &syn::LitInt) -> syn::Result<u32> {
+    let weight = lit.base10_parse()?;
+    if weight == 0 {
+        return Err(syn::Error::new_spanned(lit, "`weight` must be at least 1"));
+    }
+    Ok(weight)
+}
+
+/// Parse `bound = "..."` as a comma-separated list of `where` predicates.
+fn bound(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<Vec<syn::WherePredicate>> {
+    let lit: syn::LitStr = meta.value()?.parse()?;
+    Ok(lit
+        .parse_with(syn::punctuated::Punctuated::<_, syn::Token![,]>::parse_terminated)?
+        .into_iter()
+        .collect())
+}
+
+/// Parse one item inside `#[quickcheck(...)]` on a field (or inside `each(...)`, `keys(...)`, or `values(...)`).
+fn field_meta(out: &mut Field, meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.path.is_ident("with") {
+        set_once(&mut out.with, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("shrink_with") {
+        set_once(&mut out.shrink_with, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("skip") {
+        set_flag(&mut out.skip, meta)
+    } else if meta.path.is_ident("no_shrink") {
+        set_flag(&mut out.no_shrink, meta)
+    } else if meta.path.is_ident("range") {
+        set_once(&mut out.range, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("finite") {
+        set_flag(&mut out.finite, meta)
+    } else if meta.path.is_ident("regex") {
+        set_once(&mut out.regex, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("len") {
+        set_once(&mut out.len, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("min_len") {
+        set_once(&mut out.min_len, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("max_len") {
+        set_once(&mut out.max_len, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("non_empty") {
+        set_flag(&mut out.non_empty, meta)
+    } else if meta.path.is_ident("size") {
+        set_once(&mut out.size, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("size_scale") {
+        set_once(&mut out.size_scale, meta, meta.value()?.parse()?)
+    } else if meta.path.is_ident("each") {
+        set_once(&mut out.each, meta, Box::new(nested(meta)?))
+    } else if meta.path.is_ident("keys") {
+        set_once(&mut out.keys, meta, Box::new(nested(meta)?))
+    } else if meta.path.is_ident("values") {
+        set_once(&mut out.values, meta, Box::new(nested(meta)?))
+    } else {
+        Err(meta.error("unrecognized `quickcheck` field attribute"))
+    }
+}
+
+/// Parse a parenthesized list of field attributes, as in `each(range = 1..=65535)`.
+fn nested(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<Field> {
+    let mut out = Field::default();
+    meta.parse_nested_meta(|inner| field_meta(&mut out, &inner))?;
+    out.check(meta.path.span())?;
+    if out.has_size() {
+        return Err(meta.error(
+            "`size` and `size_scale` apply to whole fields, so they can't go inside element attributes",
+        ));
+    }
+    Ok(out)
+}
+
+/// Read all `#[quickcheck(...)]` attributes on a field, ignoring everything else.
+pub fn field(attrs: &[syn::Attribute]) -> syn::Result<Field> {
+    let mut out = Field::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("quickcheck")) {
+        attr.parse_nested_meta(|meta| field_meta(&mut out, &meta))?;
+        out.check(attr.span())?;
+    }
+    Ok(out)
+}
+
+/// Read all `#[quickcheck(...)]` attributes on the `struct` or `enum` itself, ignoring everything else.
+pub fn container(attrs: &[syn::Attribute]) -> syn::Result<Container> {
+    let mut out = Container::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("quickcheck")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                set_once(&mut out.bound, &meta, bound(&meta)?)
+            } else if meta.path.is_ident("crate") {
+                set_once(&mut out.krate, &meta, meta.value()?.parse()?)
+            } else if meta.path.is_ident("shrink_to_default") {
+                set_flag(&mut out.shrink_to_default, &meta)
+            } else if meta.path.is_ident("split_size") {
+                set_flag(&mut out.split_size, &meta)
+            } else {
+                Err(meta.error("unrecognized `quickcheck` container attribute"))
+            }
+        })?;
+    }
+    Ok(out)
+}
+
+/// Read all `#[quickcheck(...)]` attributes on an enum variant, ignoring everything else.
+pub fn variant(attrs: &[syn::Attribute]) -> syn::Result<Variant> {
+    let mut out = Variant::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("quickcheck")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("base") {
+                set_flag(&mut out.base, &meta)
+            } else if meta.path.is_ident("shrink_order") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                set_once(&mut out.shrink_order, &meta, lit.base10_parse()?)
+            } else if meta.path.is_ident("skip") {
+                set_flag(&mut out.skip, &meta)
+            } else if meta.path.is_ident("weight") {
+                set_once(&mut out.weight, &meta, weight(&meta.value()?.parse()?)?)
+            } else {
+                Err(meta.error("unrecognized `quickcheck` variant attribute"))
+            }
+        })?;
+        if out.skip && (out.base || out.weight.is_some() || out.shrink_order.is_some()) {
+            return Err(syn::Error::new(
+                attr.span(),
+                "`skip` never generates this variant, so it can't also be a `base` case or take a `weight` or `shrink_order`",
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Read all `#[quickcheck(...)]` attributes on a type parameter, ignoring everything else.
+pub fn param(attrs: &[syn::Attribute]) -> syn::Result<Param> {
+    let mut out = Param::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("quickcheck")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                set_once(&mut out.bound, &meta, bound(&meta)?)
+            } else {
+                Err(meta.error("unrecognized `quickcheck` type parameter attribute"))
+            }
+        })?;
+    }
+    Ok(out)
+}
+
+/// Read `active` and `weight` from the `#[quickcheck(...)]` attributes on a `union` field,
+/// and hand back the field's attributes without them, for `field` to read as usual.
+pub fn member(attrs: &[syn::Attribute]) -> syn::Result<(Member, Vec<syn::Attribute>)> {
+    let mut out = Member::default();
+    let mut rest = vec![];
+    for attr in attrs {
+        if !attr.path().is_ident("quickcheck") {
+            rest.push(attr.clone());
+            continue;
+        }
+        let mut kept = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::new();
+        for meta in attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        )? {
+            match meta {
+                syn::Meta::Path(ref path) if path.is_ident("active") => {
+                    if out.active {
+                        return Err(syn::Error::new_spanned(
+                            path,
+                            "duplicate `quickcheck` attribute",
+                        ));
+                    }
+                    out.active = true;
+                }
+                syn::Meta::NameValue(ref pair) if pair.path.is_ident("weight") => {
+                    if out.weight.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            pair,
+                            "duplicate `quickcheck` attribute",
+                        ));
+                    }
+                    let value = &pair.value;
+                    out.weight = Some(weight(&syn::parse2(quote! { #value })?)?);
+                }
+                syn::Meta::Path(_) | syn::Meta::List(_) | syn::Meta::NameValue(_) => {
+                    kept.push(meta);
+                }
+            }
+        }
+        if out.active && out.weight.is_some() {
+            return Err(syn::Error::new(
+                attr.span(),
+                "`active` always initializes this field, so it can't also take a `weight`",
+            ));
+        }
+        if !kept.is_empty() {
+            rest.push(syn::parse_quote! { #[quickcheck(#kept)] });
+        }
+    }
+    Ok((out, rest))
+}
@@ -27,6 +27,10 @@
     clippy::string_add
 )]
 
+mod attrs;
+mod regex;
+mod strategy;
+
 use proc_macro2::Span;
 use quote::{quote, ToTokens};
 use syn::spanned::Spanned;
@@ -88,7 +92,7 @@ macro_rules! ident {
 }
 
 /// Automatically derive `quickcheck::Arbitrary`.
-#[proc_macro_derive(QuickCheck)]
+#[proc_macro_derive(QuickCheck, attributes(quickcheck))]
 pub fn arbitrary(ts: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(ts as syn::DeriveInput);
     from_derive_input(input)
@@ -194,6 +198,18 @@ fn make_trivial_prop(ident: &syn::Ident, generics: &syn::Generics) -> syn::Resul
 fn from_derive_input(i: syn::DeriveInput) -> syn::Result<syn::ItemMod> {
     use heck::ToSnakeCase;
     let mod_name = &(i.ident.to_string().to_snake_case() + "_derive_quickcheck");
+    let container = attrs::container(&i.attrs)?;
+    let lifetimes: Vec<_> = i
+        .generics
+        .lifetimes()
+        .map(|param| param.lifetime.ident.clone())
+        .collect();
+    // Everything else carries over to the `impl`, but `#[quickcheck(...)]` and `#[repr(...)]` only mean something on the type itself
+    let attrs = i
+        .attrs
+        .into_iter()
+        .filter(|a| !a.path().is_ident("quickcheck") && !a.path().is_ident("repr"))
+        .collect();
     Ok(syn::ItemMod {
         attrs: vec![],
         vis: syn::Visibility::Inherited,
@@ -202,40 +218,324 @@ fn from_derive_input(i: syn::DeriveInput) -> syn::Result<syn::ItemMod> {
         ident: syn::Ident::new(mod_name, Span::call_site()),
         content: Some((
             delim_token!(Brace),
-            vec![
+            [
                 syn::Item::Use(syn::parse2(quote! { use super::*; })?),
-                syn::Item::Use(syn::parse2(
-                    quote! { use ::breadth_first_zip::BreadthFirstZip; },
-                )?),
                 make_trivial_prop(&i.ident, &i.generics)?,
-                syn::Item::Impl(match i.data {
-                    syn::Data::Enum(d) => from_enum(i.attrs, i.ident, i.generics, d),
-                    syn::Data::Struct(d) => from_struct(i.attrs, i.ident, i.generics, d),
-                    syn::Data::Union(d) => from_union(i.attrs, i.ident, i.generics, d),
-                }?),
-            ],
+                syn::Item::Impl(syn::parse2(staticize(
+                    match i.data {
+                        syn::Data::Enum(d) => {
+                            from_enum(attrs, &i.ident, &i.generics, d, &container)
+                        }
+                        syn::Data::Struct(d) => {
+                            from_struct(attrs, &i.ident, &i.generics, d, &container)
+                        }
+                        syn::Data::Union(d) => {
+                            from_union(attrs, &i.ident, &i.generics, &d, &container)
+                        }
+                    }?
+                    .into_token_stream(),
+                    &lifetimes,
+                ))?),
+            ]
+            .into_iter()
+            .map(|item| match container.krate {
+                None => Ok(item),
+                Some(ref krate) => syn::parse2(rebase(item.into_token_stream(), krate)),
+            })
+            .collect::<syn::Result<_>>()?,
         )),
         semi: None,
     })
 }
 
-/// Call a type's static `arbitrary` function.
-fn static_arbitrary(ty: syn::Type) -> syn::Expr {
-    let mut e: syn::ExprCall = syn::parse2(
-                        quote! { <A as ::quickcheck::Arbitrary>::arbitrary(&mut smaller_gen) },
-                    ).expect("`derive-quickcheck`-internal: Expected to be able to parse our internal implementation but couldn't");
-    let &mut syn::Expr::Path(ref mut p) = e.func.as_mut() else { panic!("`derive-quickcheck`-internal: Expected a path") }; // <A as ::quickcheck::Arbitrary>::arbitrary
-    let Some(qself) = p.qself.as_mut() else { panic!("`derive-quickcheck`-internal: Expected a qself (i.e. `<A as T>::...`)") };
-    *qself.ty.as_mut() = ty;
-    syn::Expr::Call(e)
+/// Replace every `::quickcheck` that starts a path with `#[quickcheck(crate = path)]`,
+/// so the generated code works through a renamed dependency or a crate that re-exports `quickcheck`.
+/// `::quickcheck` in the middle of a path (e.g. `facade::quickcheck`) stays as is.
+fn rebase(tokens: proc_macro2::TokenStream, krate: &syn::Path) -> proc_macro2::TokenStream {
+    let mut out = proc_macro2::TokenStream::new();
+    let mut iter = tokens.into_iter();
+    let mut after_segment = false;
+    while let Some(token) = iter.next() {
+        // Keywords like `as` or `dyn` can come right before a path, but `self`, `super`, and `crate` start one
+        let is_segment = matches!(token, proc_macro2::TokenTree::Ident(ref ident)
+            if syn::parse2::<syn::Ident>(quote! { #ident }).is_ok()
+                || matches!(ident.to_string().as_str(), "crate" | "self" | "Self" | "super"));
+        match token {
+            proc_macro2::TokenTree::Punct(ref colon)
+                if !after_segment
+                    && colon.as_char() == ':'
+                    && colon.spacing() == proc_macro2::Spacing::Joint =>
+            {
+                let mut lookahead = iter.clone();
+                if matches!(lookahead.next(), Some(proc_macro2::TokenTree::Punct(ref second)) if second.as_char() == ':')
+                    && matches!(lookahead.next(), Some(proc_macro2::TokenTree::Ident(ref name)) if name == "quickcheck")
+                {
+                    iter = lookahead;
+                    out.extend(quote! { #krate });
+                    after_segment = true;
+                    continue;
+                }
+                out.extend([token]);
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                let mut inner =
+                    proc_macro2::Group::new(group.delimiter(), rebase(group.stream(), krate));
+                inner.set_span(group.span());
+                out.extend([proc_macro2::TokenTree::Group(inner)]);
+            }
+            proc_macro2::TokenTree::Punct(_)
+            | proc_macro2::TokenTree::Ident(_)
+            | proc_macro2::TokenTree::Literal(_) => out.extend([token]),
+        }
+        after_segment = is_segment;
+    }
+    out
+}
+
+/// Replace the type's own lifetime parameters with `'static` everywhere, since `Arbitrary` needs `'static` anyway:
+/// `struct Name<'a>` gets `impl Arbitrary for Name<'static>`, and its `&'a str` fields become `&'static str`.
+fn staticize(
+    tokens: proc_macro2::TokenStream,
+    lifetimes: &[syn::Ident],
+) -> proc_macro2::TokenStream {
+    let mut out = proc_macro2::TokenStream::new();
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token {
+            proc_macro2::TokenTree::Punct(ref apostrophe) if apostrophe.as_char() == '\'' => {
+                if let Some(&proc_macro2::TokenTree::Ident(ref name)) = iter.peek() {
+                    if lifetimes.contains(name) {
+                        iter.next();
+                        out.extend(quote! { 'static });
+                        continue;
+                    }
+                }
+                out.extend([token]);
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                let mut inner = proc_macro2::Group::new(
+                    group.delimiter(),
+                    staticize(group.stream(), lifetimes),
+                );
+                inner.set_span(group.span());
+                out.extend([proc_macro2::TokenTree::Group(inner)]);
+            }
+            proc_macro2::TokenTree::Punct(_)
+            | proc_macro2::TokenTree::Ident(_)
+            | proc_macro2::TokenTree::Literal(_) => out.extend([token]),
+        }
+    }
+    out
+}
+
+/// Generate one field, either with its type's `arbitrary` or with whatever its attributes ask for.
+/// Fields draw from `own_gen` if they have one (see `split_gens`), otherwise from `smaller_gen`,
+/// or from `recursive_gen` if they mention the type itself.
+/// With `size` or `size_scale`, they draw from a `Gen` of that size instead, seeded from the one they'd have used.
+fn field_arbitrary(
+    f: &syn::Field,
+    name: &syn::Ident,
+    own_gen: Option<syn::Ident>,
+) -> syn::Result<syn::Expr> {
+    let attrs = attrs::field(&f.attrs)?;
+    let recursive = is_recursive(f, name)?;
+    let split = own_gen.is_some();
+    let gen = own_gen.unwrap_or_else(|| {
+        if recursive {
+            ident!(recursive_gen)
+        } else {
+            ident!(smaller_gen)
+        }
+    });
+    // `size_scale` needs arithmetic that `clippy::restriction` would otherwise flag in the user's crate
+    let (allow, requested) = match (attrs.size.as_ref(), attrs.size_scale.as_ref()) {
+        (Some(size), _) => (None, quote! { #size }),
+        (None, Some(scale)) => (
+            Some(quote! { #[allow(clippy::float_arithmetic)] }),
+            quote! { (g.size() as f64 * (#scale)) as usize },
+        ),
+        (None, None) => return draw(&f.ty, attrs, recursive, split, &gen),
+    };
+    // Recursive fields may only get smaller, or recursion might not end; others need at least 1 (see `field_gens`)
+    let size = if recursive {
+        quote! { ::core::cmp::min(#requested, #gen.size()) }
+    } else {
+        quote! { ::core::cmp::max(#requested, 1) }
+    };
+    let seed = seed(&quote! { &mut #gen });
+    let field = draw(&f.ty, attrs, recursive, split, &ident!(sized_gen))?;
+    syn::parse2(quote! {{
+        #allow
+        let mut sized_gen = ::quickcheck::Gen::from_size_and_seed(#size, #seed);
+        #field
+    }})
+}
+
+/// Generate one value of type `ty` from `gen`.
+/// A recursive `Option` is always `None` once `gen` runs out, and so is a recursive collection.
+/// Recursive collections, and with `split_size` all collections (unless they have `with`), split `gen` between their elements.
+fn draw(
+    ty: &syn::Type,
+    attrs: attrs::Field,
+    recursive: bool,
+    split: bool,
+    gen: &syn::Ident,
+) -> syn::Result<syn::Expr> {
+    if (recursive || split) && attrs.with.is_none() && strategy::is_collection(ty) {
+        return strategy::call(
+            &strategy::split_collection(ty, attrs, recursive)?,
+            &quote! { &mut #gen },
+        );
+    }
+    if !recursive {
+        return strategy::call(&strategy::new(ty, attrs)?.arbitrary, &quote! { &mut #gen });
+    }
+    let plain_option = attrs.with.is_none() && strategy::is_option(ty);
+    let arbitrary = strategy::call(&strategy::new(ty, attrs)?.arbitrary, &quote! { &mut #gen })?;
+    if plain_option {
+        syn::parse2(quote! {
+            if #gen.size() == 0 { None } else { #arbitrary }
+        })
+    } else {
+        Ok(arbitrary)
+    }
+}
+
+/// Whether a field mentions `Self` or the type's own name (and isn't `skip`ped).
+fn is_recursive(f: &syn::Field, name: &syn::Ident) -> syn::Result<bool> {
+    Ok(!attrs::field(&f.attrs)?.skip && strategy::mentions(&f.ty, name))
+}
+
+/// How many of these fields are recursive.
+fn count_recursive(fields: &syn::Fields, name: &syn::Ident) -> syn::Result<usize> {
+    Ok(fields
+        .iter()
+        .map(|f| is_recursive(f, name))
+        .collect::<syn::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|&recursive| recursive)
+        .count())
+}
+
+/// A uniformly random `u64` drawn from `g` (a `&mut Gen`).
+/// `u64::arbitrary` picks edge cases like `0`, `1`, and `u64::MAX` about a tenth of the time, which would skew weighted choices,
+/// but `bool::arbitrary` is a fair coin, so build the number one bit at a time.
+fn uniform(g: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        (0..64).fold(0_u64, |bits, _| {
+            (bits << 1_u32) | u64::from(<bool as ::quickcheck::Arbitrary>::arbitrary(#g))
+        })
+    }
+}
+
+/// A seed for a new `Gen`, drawn from `parent` (a `&mut Gen`).
+/// Seeding from the parent (rather than `Gen::new`) makes the whole value reproducible from the outermost `Gen`'s seed.
+/// The seed is `uniform`, since `u64::arbitrary`'s edge cases would give sibling `Gen`s the same seed now and then.
+fn seed(parent: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    uniform(parent)
+}
+
+/// Make a `Gen` named `ident` for fields, with this size and seeded from `g`.
+fn sub_gen(ident: &syn::Ident, size: &proc_macro2::TokenStream) -> syn::Result<syn::Stmt> {
+    let seed = seed(&quote! { g });
+    syn::parse2(quote! {
+        let mut #ident = ::quickcheck::Gen::from_size_and_seed(#size, #seed);
+    })
+}
+
+/// `let mut smaller_gen = ...`, `decr_size_by` smaller than `g`,
+/// and if there are any recursive fields, `let mut recursive_gen = ...`.
+/// Recursive fields split the rest of `g`'s size between them, so one value has at most about `g.size()` recursive nodes,
+/// and recursion always ends once the size runs out.
+fn field_gens(decr_size_by: usize, recursive: usize) -> syn::Result<Vec<syn::Stmt>> {
+    let decr = proc_macro2::Literal::usize_unsuffixed(decr_size_by);
+    // `quickcheck` panics generating collections at size 0, so never go below 1
+    let mut stmts = vec![sub_gen(
+        &ident!(smaller_gen),
+        &quote! { ::core::cmp::max(g.size().saturating_sub(#decr), 1) },
+    )?];
+    if recursive > 0 {
+        let split = if recursive == 1 {
+            quote! {}
+        } else {
+            let ways = proc_macro2::Literal::usize_unsuffixed(recursive);
+            quote! { .checked_div(#ways).unwrap_or_default() }
+        };
+        stmts.push(sub_gen(
+            &ident!(recursive_gen),
+            &quote! { g.size().saturating_sub(1)#split },
+        )?);
+    }
+    Ok(stmts)
+}
+
+/// With `#[quickcheck(split_size)]`, give each field its own `Gen` (`field_gen_0`, `field_gen_1`, ...),
+/// splitting `g`'s size between them at random cut points so the whole value stays about as big as `g.size()`.
+/// Recursive types keep one unit of size for themselves, so recursion still ends.
+fn split_gens(fields: &syn::Fields, name: &syn::Ident) -> syn::Result<Vec<syn::Stmt>> {
+    if fields.is_empty() {
+        return Ok(vec![]);
+    }
+    let gens: Vec<_> = (0..fields.len())
+        .map(|i| quote::format_ident!("field_gen_{i}"))
+        .collect();
+    let cuts = proc_macro2::Literal::usize_unsuffixed(fields.len().saturating_sub(1));
+    let total = if count_recursive(fields, name)? > 0 {
+        quote! { g.size().saturating_sub(1) }
+    } else {
+        quote! { g.size() }
+    };
+    let seed = seed(&quote! { g });
+    let make = fields
+        .iter()
+        .map(|f| {
+            // Recursive fields need a size of 0 to know when to stop, but `quickcheck` panics on empty collections at size 0
+            let size = if is_recursive(f, name)? {
+                quote! { shares.next().unwrap_or_default() }
+            } else {
+                quote! { ::core::cmp::max(shares.next().unwrap_or_default(), 1) }
+            };
+            Ok(quote! { ::quickcheck::Gen::from_size_and_seed(#size, #seed) })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(vec![syn::parse2(quote! {
+        let (#(mut #gens,)*) = {
+            let total = #total;
+            let mut cuts: Vec<usize> = ::core::iter::repeat_with(|| {
+                let extra = <usize as ::quickcheck::Arbitrary>::arbitrary(g);
+                total.checked_add(1).map_or(extra, |n| extra.wrapping_rem(n))
+            })
+            .take(#cuts)
+            .chain([0, total])
+            .collect();
+            cuts.sort_unstable();
+            let mut shares = cuts.iter().zip(cuts.iter().skip(1)).map(|(lo, hi)| hi.saturating_sub(*lo));
+            (#(#make,)*)
+        };
+    })?])
 }
 
 /// Call `arbitrary` on all these fields and wrap it in the appropriate `{...}` or `(...)`.
 #[allow(clippy::too_many_lines)]
-fn all_of(path: syn::Path, fields: syn::Fields) -> syn::Expr {
+fn all_of(
+    path: syn::Path,
+    fields: syn::Fields,
+    name: &syn::Ident,
+    container: &attrs::Container,
+) -> syn::Result<syn::Expr> {
     #[allow(clippy::expect_used, clippy::panic)]
     let decr_size_by = fields.len();
-    match fields {
+    let gens = if container.split_size {
+        split_gens(&fields, name)?
+    } else {
+        field_gens(decr_size_by, count_recursive(&fields, name)?)?
+    };
+    let own_gen = |i: usize| {
+        container
+            .split_size
+            .then(|| quote::format_ident!("field_gen_{i}"))
+    };
+    Ok(match fields {
         syn::Fields::Unit => syn::Expr::Path(syn::ExprPath {
             attrs: vec![],
             qself: None,
@@ -246,89 +546,9 @@ fn all_of(path: syn::Path, fields: syn::Fields) -> syn::Expr {
             label: None,
             block: syn::Block {
                 brace_token: delim_token!(Brace),
-                stmts: vec![
-                    syn::Stmt::Local(syn::Local {
-                        attrs: vec![],
-                        let_token: syn::token::Let {
-                            span: Span::call_site(),
-                        },
-                        pat: syn::Pat::Ident(syn::PatIdent {
-                            attrs: vec![],
-                            by_ref: None,
-                            mutability: Some(syn::token::Mut {
-                                span: Span::call_site(),
-                            }),
-                            ident: ident!(smaller_gen),
-                            subpat: None,
-                        }),
-                        init: Some(syn::LocalInit {
-                            eq_token: single_token!(Eq),
-                            expr: Box::new(syn::Expr::Call(syn::ExprCall {
-                                attrs: vec![],
-                                func: Box::new(syn::Expr::Path(syn::ExprPath {
-                                    attrs: vec![],
-                                    qself: None,
-                                    path: syn::Path {
-                                        leading_colon: Some(syn::token::PathSep {
-                                            spans: [Span::call_site(), Span::call_site()],
-                                        }),
-                                        segments: punctuate![
-                                            syn::PathSegment {
-                                                ident: ident!(quickcheck),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                            syn::PathSegment {
-                                                ident: ident!(Gen),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                            syn::PathSegment {
-                                                ident: ident!(new),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                        ],
-                                    },
-                                })),
-                                paren_token: delim_token!(Paren),
-                                args: punctuate![syn::Expr::MethodCall(syn::ExprMethodCall {
-                                    attrs: vec![],
-                                    receiver: Box::new(syn::Expr::MethodCall(
-                                        syn::ExprMethodCall {
-                                            attrs: vec![],
-                                            receiver: Box::new(syn::Expr::Path(syn::ExprPath {
-                                                attrs: vec![],
-                                                qself: None,
-                                                path: syn::Path {
-                                                    leading_colon: None,
-                                                    segments: punctuate![syn::PathSegment {
-                                                        ident: ident!(g),
-                                                        arguments: syn::PathArguments::None
-                                                    }]
-                                                }
-                                            })),
-                                            dot_token: single_token!(Dot),
-                                            method: ident!(size),
-                                            turbofish: None,
-                                            paren_token: delim_token!(Paren),
-                                            args: punctuate![],
-                                        }
-                                    )),
-                                    dot_token: single_token!(Dot),
-                                    method: ident!(saturating_sub),
-                                    turbofish: None,
-                                    paren_token: delim_token!(Paren),
-                                    args: punctuate![syn::Expr::Lit(syn::ExprLit {
-                                        attrs: vec![],
-                                        lit: syn::Lit::Verbatim(
-                                            proc_macro2::Literal::usize_unsuffixed(decr_size_by)
-                                        )
-                                    })]
-                                })],
-                            })),
-                            diverge: None,
-                        }),
-                        semi_token: single_token!(Semi),
-                    }),
-                    syn::Stmt::Expr(
+                stmts: gens
+                    .into_iter()
+                    .chain([syn::Stmt::Expr(
                         syn::Expr::Call(syn::ExprCall {
                             attrs: vec![],
                             func: Box::new(syn::Expr::Path(syn::ExprPath {
@@ -340,12 +560,13 @@ fn all_of(path: syn::Path, fields: syn::Fields) -> syn::Expr {
                             args: members
                                 .unnamed
                                 .into_iter()
-                                .map(move |f| static_arbitrary(f.ty))
-                                .collect(),
+                                .enumerate()
+                                .map(|(i, f)| field_arbitrary(&f, name, own_gen(i)))
+                                .collect::<syn::Result<_>>()?,
                         }),
                         None,
-                    ),
-                ],
+                    )])
+                    .collect(),
             },
         }),
         syn::Fields::Named(members) => syn::Expr::Block(syn::ExprBlock {
@@ -353,74 +574,9 @@ fn all_of(path: syn::Path, fields: syn::Fields) -> syn::Expr {
             label: None,
             block: syn::Block {
                 brace_token: delim_token!(Brace),
-                stmts: vec![
-                    syn::Stmt::Local(syn::Local {
-                        attrs: vec![],
-                        let_token: syn::token::Let {
-                            span: Span::call_site(),
-                        },
-                        pat: syn::Pat::Ident(syn::PatIdent {
-                            attrs: vec![],
-                            by_ref: None,
-                            mutability: Some(syn::token::Mut {
-                                span: Span::call_site(),
-                            }),
-                            ident: ident!(smaller_gen),
-                            subpat: None,
-                        }),
-                        init: Some(syn::LocalInit {
-                            eq_token: single_token!(Eq),
-                            expr: Box::new(syn::Expr::Call(syn::ExprCall {
-                                attrs: vec![],
-                                func: Box::new(syn::Expr::Path(syn::ExprPath {
-                                    attrs: vec![],
-                                    qself: None,
-                                    path: syn::Path {
-                                        leading_colon: Some(syn::token::PathSep {
-                                            spans: [Span::call_site(), Span::call_site()],
-                                        }),
-                                        segments: punctuate![
-                                            syn::PathSegment {
-                                                ident: ident!(quickcheck),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                            syn::PathSegment {
-                                                ident: ident!(Gen),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                            syn::PathSegment {
-                                                ident: ident!(new),
-                                                arguments: syn::PathArguments::None
-                                            },
-                                        ],
-                                    },
-                                })),
-                                paren_token: delim_token!(Paren),
-                                args: punctuate![syn::Expr::MethodCall(syn::ExprMethodCall {
-                                    attrs: vec![],
-                                    receiver: Box::new(syn::Expr::Path(syn::ExprPath {
-                                        attrs: vec![],
-                                        qself: None,
-                                        path: syn::Path {
-                                            leading_colon: None,
-                                            segments: punctuate![syn::PathSegment {
-                                                ident: ident!(g),
-                                                arguments: syn::PathArguments::None
-                                            }]
-                                        }
-                                    })),
-                                    dot_token: single_token!(Dot),
-                                    method: ident!(size),
-                                    turbofish: None,
-                                    paren_token: delim_token!(Paren),
-                                    args: punctuate![],
-                                })],
-                            })),
-                            diverge: None,
-                        }),
-                        semi_token: single_token!(Semi),
-                    }),
-                    syn::Stmt::Expr(
+                stmts: gens
+                    .into_iter()
+                    .chain([syn::Stmt::Expr(
                         syn::Expr::Struct(syn::ExprStruct {
                             attrs: vec![],
                             qself: None,
@@ -429,37 +585,44 @@ fn all_of(path: syn::Path, fields: syn::Fields) -> syn::Expr {
                             fields: members
                                 .named
                                 .into_iter()
-                                .map(move |f| syn::FieldValue {
-                                    attrs: f.attrs,
-                                    member: f.ident.map_or_else(
-                                        || {
-                                            syn::Member::Unnamed(syn::Index {
-                                                index: u32::MAX,
-                                                span: f.ty.span(),
-                                            })
-                                        },
-                                        syn::Member::Named,
-                                    ),
-                                    colon_token: f.colon_token,
-                                    expr: static_arbitrary(f.ty),
+                                .enumerate()
+                                .map(|(i, f)| {
+                                    Ok(syn::FieldValue {
+                                        attrs: vec![],
+                                        member: f.ident.clone().map_or_else(
+                                            || {
+                                                syn::Member::Unnamed(syn::Index {
+                                                    index: u32::MAX,
+                                                    span: f.ty.span(),
+                                                })
+                                            },
+                                            syn::Member::Named,
+                                        ),
+                                        colon_token: f.colon_token,
+                                        expr: field_arbitrary(&f, name, own_gen(i))?,
+                                    })
                                 })
-                                .collect(),
+                                .collect::<syn::Result<_>>()?,
                             dot2_token: None,
                             rest: None,
                         }),
                         None,
-                    ),
-                ],
+                    )])
+                    .collect(),
             },
         }),
-    }
+    })
 }
 
 /// Choose one of many variants and call `arbitrary` on all its members.
+/// Once the size runs out (`exhausted`), leave out recursive variants too, so recursion ends.
 #[allow(clippy::too_many_lines)]
 fn one_of(
     variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
     max_len: usize,
+    exhausted: bool,
+    name: &syn::Ident,
+    container: &attrs::Container,
 ) -> syn::Result<syn::Expr> {
     if variants.is_empty() {
         bail!(
@@ -521,11 +684,42 @@ fn one_of(
             })),
         ),
     });
-    let elems = variants
+    let generated = variants
         .into_iter()
-        .filter(|v| v.fields.len() <= max_len)
         .map(|v| {
-            syn::Expr::Cast(syn::ExprCast {
+            Ok((
+                v,
+                attrs::variant(&v.attrs)?,
+                count_recursive(&v.fields, name)? > 0,
+            ))
+        })
+        .filter(|candidate| !matches!(*candidate, Ok((_, ref a, _)) if a.skip))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let mut candidates: Vec<_> = generated
+        .iter()
+        .filter(|&&(v, _, recursive)| v.fields.len() <= max_len && !(exhausted && recursive))
+        .collect();
+    if candidates.is_empty() {
+        // Nothing fits this size, so fall back to the base cases, or failing that, the smallest variants (that don't recurse, if possible)
+        candidates = generated.iter().filter(|&&(_, ref a, _)| a.base).collect();
+    }
+    if candidates.is_empty() {
+        let any_finite = generated.iter().any(|&(_, _, recursive)| !recursive);
+        let fewest = generated
+            .iter()
+            .filter(|&&(_, _, recursive)| !(any_finite && recursive))
+            .map(|&(v, _, _)| v.fields.len())
+            .min()
+            .unwrap_or(0);
+        candidates = generated
+            .iter()
+            .filter(|&&(v, _, recursive)| v.fields.len() == fewest && !(any_finite && recursive))
+            .collect();
+    }
+    let elems: syn::punctuated::Punctuated<_, syn::token::Comma> = candidates
+        .iter()
+        .map(|&&(v, _, _)| {
+            Ok(syn::Expr::Cast(syn::ExprCast {
                 attrs: vec![],
                 expr: Box::new(syn::Expr::Paren(syn::ExprParen {
                     attrs: vec![],
@@ -564,16 +758,25 @@ fn one_of(
                                 ],
                             },
                             v.fields.clone(),
-                        )),
+                            name,
+                            container,
+                        )?),
                     })),
                 })),
                 as_token: syn::token::As {
                     span: Span::call_site(),
                 },
                 ty: Box::new(fn_type.clone()),
-            })
+            }))
         })
-        .collect();
+        .collect::<syn::Result<_>>()?;
+    if candidates.iter().any(|&&(_, ref a, _)| a.weight.is_some()) {
+        return weighted(
+            candidates.iter().map(|&&(_, ref a, _)| a.weight()),
+            elems,
+            variants.span(),
+        );
+    }
     Ok(syn::Expr::Call(syn::ExprCall {
         attrs: vec![],
         func: Box::new(syn::Expr::MethodCall(syn::ExprMethodCall {
@@ -634,6 +837,44 @@ fn one_of(
     }))
 }
 
+/// Call one of these generators, each as often as its weight says, by matching a random number against consecutive ranges.
+fn weighted(
+    weights: impl Iterator<Item = u32>,
+    elems: syn::punctuated::Punctuated<syn::Expr, syn::token::Comma>,
+    span: Span,
+) -> syn::Result<syn::Expr> {
+    let mut total: u64 = 0;
+    let mut ranges = vec![];
+    for (weight, elem) in weights.zip(elems) {
+        let lo = total;
+        total = total
+            .checked_add(weight.into())
+            .filter(|&sum| sum <= u32::MAX.into())
+            .ok_or_else(|| syn::Error::new(span, "Weights add up to more than `u32::MAX`"))?;
+        ranges.push((lo, total.saturating_sub(1), elem));
+    }
+    // The last range ends at `total - 1` anyway, so make it a catch-all to keep the match exhaustive
+    let Some((_, _, last)) = ranges.pop() else {
+        bail!(span, "Need at least one variant to instantiate the value")
+    };
+    let arms = ranges.into_iter().map(|(lo, hi, elem)| {
+        let (start, end) = (
+            proc_macro2::Literal::u64_unsuffixed(lo),
+            proc_macro2::Literal::u64_unsuffixed(hi),
+        );
+        quote! { #start..=#end => (#elem)(g), }
+    });
+    let modulus = proc_macro2::Literal::u64_unsuffixed(total);
+    // A `u64` is so much wider than any total weight that `% total` is as good as uniform
+    let draw = uniform(&quote! { g });
+    syn::parse2(quote! {
+        match (#draw).wrapping_rem(#modulus) {
+            #(#arms)*
+            _ => (#last)(g),
+        }
+    })
+}
+
 /// `GenericParam` to `GenericArgument`.
 fn param2arg(p: syn::GenericParam) -> syn::GenericArgument {
     match p {
@@ -664,30 +905,97 @@ fn param2arg(p: syn::GenericParam) -> syn::GenericArgument {
     }
 }
 
-/// Add `: ::quickcheck::Arbitrary` to each type parameter.
-fn constrain_generics(generics: &syn::Generics) -> syn::Generics {
-    syn::Generics {
-        params: generics
-            .params
-            .iter()
-            .map(move |p| match p {
-                &syn::GenericParam::Type(ref t) => syn::GenericParam::Type(syn::TypeParam {
-                    bounds: {
-                        let mut b = t.bounds.clone();
-                        b.push(syn::TypeParamBound::Trait(
-                            syn::parse2(quote! { ::quickcheck::Arbitrary }).expect("`derive-quickcheck`-internal: Expected to be able to parse `::quickcheck::Arbitrary` but couldn't."),
-                        ));
-                        b
-                    },
-                    eq_token: None,
-                    default: None,
-                    ..t.clone()
-                }),
-                &syn::GenericParam::Lifetime(_) | &syn::GenericParam::Const(_) => p.clone(),
-            })
-            .collect(),
-        ..generics.clone()
+/// Require whatever the fields need, as `serde` and the standard derives do: `FieldType: Arbitrary`
+/// (or `Default` for `skip`) for each field type that mentions a type parameter, and `Clone + 'static` of the type itself,
+/// so parameters used only in markers or not at all don't need anything.
+/// `#[quickcheck(bound = "...")]` on the type replaces all of that, and on a parameter replaces whatever mentions it.
+fn constrain_generics(
+    generics: &syn::Generics,
+    container: &attrs::Container,
+    fields: &[&syn::Field],
+    name: &syn::Ident,
+    self_ty: &syn::Type,
+) -> syn::Result<syn::Generics> {
+    let mut out = generics.clone();
+    let mut predicates = container.bound.clone().unwrap_or_default();
+    let mut free = vec![];
+    let mut overridden = vec![];
+    for param in &mut out.params {
+        if let syn::GenericParam::Type(ref mut t) = *param {
+            // `#[quickcheck(...)]` means nothing outside the derive
+            let attrs = attrs::param(&t.attrs)?;
+            t.attrs.retain(|a| !a.path().is_ident("quickcheck"));
+            t.eq_token = None;
+            t.default = None;
+            if let Some(bound) = attrs.bound {
+                predicates.extend(bound);
+                overridden.push(t.ident.clone());
+            } else {
+                free.push(t.ident.clone());
+            }
+        }
+    }
+    if container.bound.is_none() && !(free.is_empty() && overridden.is_empty()) {
+        let free_refs: Vec<_> = free.iter().collect();
+        let overridden_refs: Vec<_> = overridden.iter().collect();
+        for f in fields {
+            let needs = if is_recursive(f, name)? {
+                recursive_bounds(&f.ty, name)?
+            } else {
+                strategy::bounds(&f.ty, &attrs::field(&f.attrs)?)?
+            };
+            predicates.extend(needs.into_iter().filter(|predicate| {
+                matches!(
+                    *predicate,
+                    syn::WherePredicate::Type(ref p)
+                        if strategy::mentions_any(&p.bounded_ty, &free_refs)
+                            && !strategy::mentions_any(&p.bounded_ty, &overridden_refs)
+                )
+            }));
+        }
+        let default = container.shrink_to_default.then(|| {
+            quote! { + ::core::default::Default + ::core::cmp::PartialEq }
+        });
+        predicates.push(syn::parse2(
+            quote! { #self_ty: ::core::clone::Clone + 'static #default },
+        )?);
+    }
+    // `Arbitrary` needs `'static`, so lifetime parameters become `'static` (see `staticize`)
+    out.params = out
+        .params
+        .into_iter()
+        .filter(|param| !matches!(*param, syn::GenericParam::Lifetime(_)))
+        .collect();
+    if out.params.is_empty() {
+        out.lt_token = None;
+        out.gt_token = None;
+    }
+    // Fields of the same type need the same bounds, so only write each once
+    let mut seen = std::collections::HashSet::new();
+    predicates.retain(|predicate| seen.insert(quote! { #predicate }.to_string()));
+    if !predicates.is_empty() {
+        out.make_where_clause().predicates.extend(predicates);
     }
+    Ok(out)
+}
+
+/// A recursive field needs no bound of its own (that's the `impl` we're writing), only bounds for whatever else it holds,
+/// e.g. `T: Arbitrary` for `Vec<(T, Box<Self>)>`.
+fn recursive_bounds(ty: &syn::Type, name: &syn::Ident) -> syn::Result<Vec<syn::WherePredicate>> {
+    let inner: Vec<&syn::Type> = if let syn::Type::Tuple(ref tuple) = *ty {
+        tuple.elems.iter().collect()
+    } else {
+        strategy::type_args(ty)
+    };
+    let mut out = vec![];
+    for t in inner {
+        if strategy::mentions(t, name) {
+            out.extend(recursive_bounds(t, name)?);
+        } else {
+            out.push(syn::parse2(quote! { #t: ::quickcheck::Arbitrary })?);
+        }
+    }
+    Ok(out)
 }
 
 /// Write `Self<A, ...>` after `impl<A: ...>`
@@ -749,55 +1057,216 @@ fn make_shrink_fn(stmts: Vec<syn::Stmt>) -> syn::Result<syn::ImplItem> {
     }))
 }
 
+/// Shrink one field bound to `binding`, either with its type's `shrink` or with whatever its attributes ask for, or `None` if it's held fixed.
+fn field_shrink(
+    f: &syn::Field,
+    binding: &syn::Ident,
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    strategy::new(&f.ty, attrs::field(&f.attrs)?)?
+        .shrink
+        .map(|shrink| Ok(strategy::call(&shrink, &quote! { #binding })?.into_token_stream()))
+        .transpose()
+}
+
+/// Build (or match) a variant or `struct` at `path` with these values for its fields, in order.
+fn construct(
+    path: &proc_macro2::TokenStream,
+    fields: &syn::Fields,
+    values: impl Iterator<Item = proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let names = fields.iter().map(|f| &f.ident);
+    match *fields {
+        syn::Fields::Named(_) => quote! { #path { #(#names: #values),* } },
+        syn::Fields::Unnamed(_) => quote! { #path(#(#values),*) },
+        syn::Fields::Unit => quote! { #path },
+    }
+}
+
+/// Bind each field of a variant (or of a `struct`) to `_0`, `_1`, ... and shrink one field at a time, holding the rest fixed.
+/// Before any of that, try everything in `first`, in order, then any direct subterms of type `self_ty`
+/// (in `Box<Self>`, `Option<Box<Self>>`, or `Vec<Self>` fields), since those are often the best shrinks of all.
+fn shrink_arm(
+    path: &syn::Path,
+    fields: &syn::Fields,
+    self_ty: &syn::Type,
+    first: &[proc_macro2::TokenStream],
+) -> syn::Result<syn::Arm> {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| syn::Ident::new(&format!("_{i:}"), Span::call_site()))
+        .collect();
+    let pat = construct(
+        &quote! { #path },
+        fields,
+        bindings.iter().map(|b| quote! { #b }),
+    );
+    let mut streams: Vec<_> = fields
+        .iter()
+        .zip(&bindings)
+        .filter_map(|(f, binding)| strategy::subterms(&f.ty, self_ty, binding))
+        .collect();
+    // Then one candidate stream per field: shrink that field and clone the others into place
+    for (f, shrunk) in fields.iter().zip(&bindings) {
+        let Some(shrinker) = field_shrink(f, shrunk)? else {
+            continue;
+        };
+        let others: Vec<_> = bindings.iter().filter(|&b| b != shrunk).collect();
+        let values = bindings.iter().map(|b| {
+            if b == shrunk {
+                quote! { #b }
+            } else {
+                quote! { #b.clone() }
+            }
+        });
+        let rebuilt = construct(&quote! { #path }, fields, values);
+        streams.push(quote! {
+            {
+                let (#(#others,)*) = (#(#others.clone(),)*);
+                #shrinker.map(move |#shrunk| #rebuilt)
+            }
+        });
+    }
+    let body = if first.is_empty() && streams.is_empty() {
+        quote! { ::quickcheck::empty_shrinker() }
+    } else {
+        quote! { Box::new([#(#first),*].into_iter()#(.chain(#streams))*) }
+    };
+    syn::parse2(quote! { #pat => #body, })
+}
+
+/// For each variant, the simpler variants to shrink it into, simplest first.
+/// Only variants we can build without any values qualify: those whose fields are all `skip` (or that have none).
+/// Each variant shrinks into those that come before it in `shrink_order` (by default, the `#[default]` variant and then declaration order),
+/// and a variant we can't build that way also shrinks into every fieldless variant, wherever it comes.
+/// Buildable variants only ever shrink into earlier ones, so shrinking can't go around in circles.
+fn simpler_variants(
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::Token![,]>,
+) -> syn::Result<Vec<Vec<proc_macro2::TokenStream>>> {
+    let mut ranked = vec![];
+    for (position, v) in variants.iter().enumerate() {
+        let attrs = attrs::variant(&v.attrs)?;
+        let mut buildable = true;
+        for f in &v.fields {
+            buildable &= attrs::field(&f.attrs)?.skip;
+        }
+        let variant = &v.ident;
+        let value = construct(
+            &quote! { Self::#variant },
+            &v.fields,
+            v.fields.iter().map(|_| quote! { Default::default() }),
+        );
+        // Without a `shrink_order` of its own, the `#[default]` variant comes before all the others
+        let default = attrs.shrink_order.is_none()
+            && v.attrs.iter().any(|attr| attr.path().is_ident("default"));
+        ranked.push((
+            (!default, attrs.shrink_order.unwrap_or(position), position),
+            !attrs.skip && buildable,
+            v.fields.is_empty(),
+            value,
+        ));
+    }
+    let mut order: Vec<_> = ranked.iter().collect();
+    order.sort_by_key(|&&(rank, ..)| rank);
+    Ok(ranked
+        .iter()
+        .map(|&(rank, buildable, _, _)| {
+            order
+                .iter()
+                .filter(|&&&(other, other_buildable, fieldless, _)| {
+                    other_buildable && (other < rank || (!buildable && fieldless))
+                })
+                .map(|&&(_, _, _, ref value)| value.clone())
+                .collect()
+        })
+        .collect())
+}
+
+/// The body of `shrink`, given the usual `match self { ... }`.
+/// With `#[quickcheck(shrink_to_default)]`, try `Default::default()` before anything else,
+/// and treat it as the smallest value, so shrinking can't go back and forth between it and its own shrinks.
+fn shrink_body(shrinks: syn::Expr, container: &attrs::Container) -> syn::Result<Vec<syn::Stmt>> {
+    if !container.shrink_to_default {
+        return Ok(vec![syn::Stmt::Expr(shrinks, None)]);
+    }
+    Ok(syn::parse2::<syn::Block>(quote! {{
+        let default = <Self as ::core::default::Default>::default();
+        if *self == default {
+            ::quickcheck::empty_shrinker()
+        } else {
+            Box::new(::core::iter::once(default).chain(#shrinks))
+        }
+    }})?
+    .stmts)
+}
+
 /// Implement for an `enum`.
 #[allow(clippy::too_many_lines)]
 fn from_enum(
     attrs: Vec<syn::Attribute>,
-    ident: syn::Ident,
-    generics: syn::Generics,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
     d: syn::DataEnum,
+    container: &attrs::Container,
 ) -> syn::Result<syn::ItemImpl> {
+    let mut generated = vec![];
+    for v in &d.variants {
+        if !attrs::variant(&v.attrs)?.skip {
+            generated.extend(&v.fields);
+        }
+    }
+    let self_ty = make_self_ty(ident.clone(), generics.clone());
+    let constrained = constrain_generics(generics, container, &generated, ident, &self_ty)?;
     Ok(syn::ItemImpl {
         attrs,
         defaultness: None,
         unsafety: None,
         impl_token: syn::parse2(quote! { impl })?,
-        generics: constrain_generics(&generics),
+        generics: constrained,
         trait_: Some((
             None,
             syn::parse2(quote! { ::quickcheck::Arbitrary })?,
             syn::parse2(quote! { for })?,
         )),
-        self_ty: Box::new(make_self_ty(ident, generics)),
+        self_ty: Box::new(self_ty.clone()),
         brace_token: delim_token!(Brace),
-        items:
-            vec![
-                make_arbitrary_fn(vec![syn::Stmt::Expr(
-                    syn::Expr::Match(syn::ExprMatch {
-                        attrs: vec![],
-                        match_token: syn::parse2(quote! { match })?,
-                        expr: Box::new(syn::parse2(quote! { g.size() })?),
-                        brace_token: delim_token!(Brace),
-                        arms: {
-                            let most_fields =
-                                d.variants.iter().fold(0, |acc, v| acc.max(v.fields.len()));
-                            let mut arms = vec![];
-                            if most_fields > 0 {
-                                arms.push(syn::Arm {
+        items: vec![
+            make_arbitrary_fn(vec![syn::Stmt::Expr(
+                syn::Expr::Match(syn::ExprMatch {
+                    attrs: vec![],
+                    match_token: syn::parse2(quote! { match })?,
+                    expr: Box::new(syn::parse2(quote! { g.size() })?),
+                    brace_token: delim_token!(Brace),
+                    arms: {
+                        let mut most_fields = 0;
+                        let mut any_generated = false;
+                        for v in &d.variants {
+                            if !attrs::variant(&v.attrs)?.skip {
+                                most_fields = most_fields.max(v.fields.len());
+                                any_generated = true;
+                            }
+                        }
+                        if !any_generated && !d.variants.is_empty() {
+                            bail!(
+                                d.variants.span(),
+                                "Every variant is marked `skip`, so there's nothing to generate"
+                            )
+                        }
+                        let mut arms = vec![];
+                        if most_fields > 0 {
+                            arms.push(syn::Arm {
+                                attrs: vec![],
+                                pat: syn::Pat::Lit(syn::ExprLit {
                                     attrs: vec![],
-                                    pat: syn::Pat::Lit(syn::ExprLit {
-                                        attrs: vec![],
-                                        lit: syn::Lit::Verbatim(
-                                            proc_macro2::Literal::usize_unsuffixed(0),
-                                        ),
-                                    }),
-                                    guard: None,
-                                    fat_arrow_token: syn::parse2(quote! { => })?,
-                                    body: Box::new(one_of(&d.variants, 0)?),
-                                    comma: Some(syn::parse2(quote! { , })?),
-                                });
-                                for i in 0..most_fields {
-                                    arms.push(syn::Arm {
+                                    lit: syn::Lit::Verbatim(
+                                        proc_macro2::Literal::usize_unsuffixed(0),
+                                    ),
+                                }),
+                                guard: None,
+                                fat_arrow_token: syn::parse2(quote! { => })?,
+                                body: Box::new(one_of(&d.variants, 0, true, ident, container)?),
+                                comma: Some(syn::parse2(quote! { , })?),
+                            });
+                            for i in 0..most_fields {
+                                arms.push(syn::Arm {
                                                 attrs: vec![],
                                                 pat: syn::Pat::Lit(syn::ExprLit {
                                                     attrs: vec![],
@@ -814,471 +1283,207 @@ fn from_enum(
                                                 }),
                                                 guard: None,
                                                 fat_arrow_token: syn::parse2(quote! { => })?,
-                                                body: Box::new(one_of(&d.variants, i)?),
+                                                body: Box::new(one_of(&d.variants, i, false, ident, container)?),
                                                 comma: Some(syn::parse2(quote! { , })?),
                                             });
-                                }
                             }
-                            arms.push(syn::Arm {
-                                attrs: vec![],
-                                pat: syn::Pat::Wild(syn::PatWild {
-                                    attrs: vec![],
-                                    underscore_token: syn::parse2(quote! { _ })?,
-                                }),
-                                guard: None,
-                                fat_arrow_token: syn::parse2(quote! { => })?,
-                                body: Box::new(one_of(&d.variants, usize::MAX)?),
-                                comma: Some(syn::parse2(quote! { , })?),
-                            });
-                            arms
-                        },
-                    }),
-                    None,
-                )])?,
-                make_shrink_fn(vec![syn::Stmt::Expr(
-                    syn::Expr::Match(syn::ExprMatch {
-                        attrs: vec![],
-                        match_token: syn::parse2(quote! { match })?,
-                        expr: Box::new(syn::Expr::Path(syn::ExprPath {
+                        }
+                        arms.push(syn::Arm {
                             attrs: vec![],
-                            qself: None,
-                            path: syn::Path {
-                                leading_colon: None,
-                                segments: punctuate![syn::PathSegment {
-                                    ident: ident!(self),
-                                    arguments: syn::PathArguments::None
-                                }],
-                            },
-                        })),
-                        brace_token: delim_token!(Brace),
-                        arms: d
-                            .variants
-                            .into_iter()
-                            .map(|v| match v.fields {
-                                fields @ syn::Fields::Named(_) => syn::Arm {
-                                    attrs: vec![],
-                                    pat: syn::Pat::TupleStruct(syn::PatTupleStruct {
-                                        attrs: vec![],
-                                        qself: None,
-                                        path: syn::Path {
-                                            leading_colon: None,
-                                            segments: punctuate![
-                                                syn::PathSegment {
-                                                    ident: ident!(Self),
-                                                    arguments: syn::PathArguments::None
-                                                },
-                                                syn::PathSegment {
-                                                    ident: v.ident.clone(),
-                                                    arguments: syn::PathArguments::None
-                                                }
-                                            ],
-                                        },
-                                        paren_token: delim_token!(Paren),
-                                        elems: fields
-                                            .iter()
-                                            .map(|f| {
-                                                syn::Pat::Path(syn::ExprPath {
-                                                    attrs: vec![],
-                                                    qself: None,
-                                                    path: syn::Path {
-                                                        leading_colon: None,
-                                                        segments: punctuate![syn::PathSegment {
-                                                            ident: f.ident.as_ref().expect(
-                                                                "Expected all elements to be named"
-                                                            ).clone(),
-                                                            arguments: syn::PathArguments::None
-                                                        }],
-                                                    },
-                                                })
-                                            })
-                                            .collect(),
-                                    }),
-                                    guard: None,
-                                    fat_arrow_token: syn::token::FatArrow {
-                                        spans: [Span::call_site(), Span::call_site()],
-                                    },
-                                    body: Box::new(syn::Expr::Struct(syn::ExprStruct {
-                                        attrs: vec![],
-                                        qself: None,
-                                        path: syn::Path {
-                                            leading_colon: None,
-                                            segments: punctuate![
-                                                syn::PathSegment {
-                                                    ident: ident!(Self),
-                                                    arguments: syn::PathArguments::None
-                                                },
-                                                syn::PathSegment {
-                                                    ident: v.ident,
-                                                    arguments: syn::PathArguments::None
-                                                }
-                                            ],
-                                        },
-                                        brace_token: delim_token!(Brace),
-                                        fields: fields
-                                            .into_iter()
-                                            .enumerate()
-                                            .map(|(i, f)| syn::FieldValue {
-                                                attrs: vec![],
-                                                member: f.ident.map_or_else(
-                                                    || {
-                                                        syn::Member::Unnamed(syn::Index {
-                                                            index: i.try_into().expect("Ridiculously large number of items in a struct"),
-                                                            span: Span::call_site(),
-                                                        })
-                                                    },
-                                                    syn::Member::Named,
-                                                ),
-                                                colon_token: Some(single_token!(Colon)),
-                                                expr: syn::Expr::MethodCall(syn::ExprMethodCall {
-                                                    attrs: vec![],
-                                                    receiver: Box::new(syn::Expr::Path(syn::ExprPath {
-                                                        attrs: vec![],
-                                                        qself: None,
-                                                        path: syn::Path {
-                                                            leading_colon: None,
-                                                            segments: punctuate![syn::PathSegment {
-                                                                ident: syn::Ident::new(&format!("_{i:}"), Span::call_site()),
-                                                                arguments: syn::PathArguments::None,
-                                                            }],
-                                                        },
-                                                    })),
-                                                    dot_token: single_token!(Dot),
-                                                    method: ident!(shrink),
-                                                    turbofish: None,
-                                                    paren_token: delim_token!(Paren),
-                                                    args: punctuate![],
-                                                }),
-                                            })
-                                            .collect(),
-                                        dot2_token: None,
-                                        rest: None,
-                                    })),
-                                    comma: Some(single_token!(Comma)),
-                                },
-                                fields @ syn::Fields::Unnamed(_) => syn::Arm {
-                                    attrs: vec![],
-                                    pat: syn::Pat::TupleStruct(syn::PatTupleStruct {
-                                        attrs: vec![],
-                                        qself: None,
-                                        path: syn::Path {
-                                            leading_colon: None,
-                                            segments: punctuate![
-                                                syn::PathSegment {
-                                                    ident: ident!(Self),
-                                                    arguments: syn::PathArguments::None
-                                                },
-                                                syn::PathSegment {
-                                                    ident: v.ident.clone(),
-                                                    arguments: syn::PathArguments::None
-                                                }
-                                            ],
-                                        },
-                                        paren_token: delim_token!(Paren),
-                                        elems: (0..fields.len())
-                                            .map(|i| {
-                                                syn::Pat::Path(syn::ExprPath {
-                                                    attrs: vec![],
-                                                    qself: None,
-                                                    path: syn::Path {
-                                                        leading_colon: None,
-                                                        segments: punctuate![syn::PathSegment {
-                                                            ident: syn::Ident::new(
-                                                                &format!("_{i:}"),
-                                                                Span::call_site()
-                                                            ),
-                                                            arguments: syn::PathArguments::None
-                                                        }],
-                                                    },
-                                                })
-                                            })
-                                            .collect(),
-                                    }),
-                                    guard: None,
-                                    fat_arrow_token: syn::token::FatArrow {
-                                        spans: [Span::call_site(), Span::call_site()],
-                                    },
-                                    body: Box::new(syn::Expr::Call(syn::ExprCall {
-                                        attrs: vec![],
-                                        func: Box::new(syn::Expr::Path(syn::ExprPath {
-                                            attrs: vec![],
-                                            qself: None,
-                                            path: syn::Path {
-                                                leading_colon: None,
-                                                segments: punctuate![
-                                                    syn::PathSegment {
-                                                        ident: syn::Ident::new("Box", Span::call_site()),
-                                                        arguments: syn::PathArguments::None,
-                                                    },
-                                                    syn::PathSegment {
-                                                        ident: syn::Ident::new("new", Span::call_site()),
-                                                        arguments: syn::PathArguments::None,
-                                                    },
-                                                ],
-                                            },
-                                        })),
-                                        paren_token: delim_token!(Paren),
-                                        args: punctuate![syn::Expr::MethodCall(syn::ExprMethodCall {
-                                            attrs: vec![],
-                                            receiver: Box::new(syn::Expr::MethodCall(syn::ExprMethodCall {
-                                                attrs: vec![],
-                                                receiver: Box::new(syn::Expr::Tuple(syn::ExprTuple {
-                                                    attrs: vec![],
-                                                    paren_token: delim_token!(Paren),
-                                                    elems: (0..fields.len())
-                                                        .map(|i| {
-                                                            syn::Expr::MethodCall(syn::ExprMethodCall {
-                                                                attrs: vec![],
-                                                                receiver: Box::new(syn::Expr::Path(syn::ExprPath {
-                                                                    attrs: vec![],
-                                                                    qself: None,
-                                                                    path: syn::Path {
-                                                                        leading_colon: None,
-                                                                        segments: punctuate![syn::PathSegment {
-                                                                            ident: syn::Ident::new(&format!("_{i:}"), Span::call_site()),
-                                                                            arguments: syn::PathArguments::None,
-                                                                        }],
-                                                                    },
-                                                                })),
-                                                                dot_token: single_token!(Dot),
-                                                                method: ident!(shrink),
-                                                                turbofish: None,
-                                                                paren_token: delim_token!(Paren),
-                                                                args: punctuate![],
-                                                            })
-                                                        })
-                                                        .collect(),
-                                                })),
-                                                dot_token: single_token!(Dot),
-                                                method: ident!(breadth_first),
-                                                turbofish: None,
-                                                paren_token: delim_token!(Paren),
-                                                args: punctuate![],
-                                            })),
-                                            dot_token: single_token!(Dot),
-                                            method: ident!(map),
-                                            turbofish: None,
-                                            paren_token: delim_token!(Paren),
-                                            args: punctuate![syn::Expr::Closure(syn::ExprClosure {
-                                                attrs: vec![],
-                                                lifetimes: None,
-                                                constness: None,
-                                                movability: None,
-                                                asyncness: None,
-                                                capture: None,
-                                                or1_token: single_token!(Or),
-                                                inputs: punctuate![syn::Pat::Tuple(syn::PatTuple {
-                                                    attrs: vec![],
-                                                    paren_token: delim_token!(Paren),
-                                                    elems: (0..fields.len()).map(|i| syn::Pat::Ident(syn::PatIdent {
-                                                        attrs: vec![],
-                                                        by_ref: None,
-                                                        mutability: None,
-                                                        ident: syn::Ident::new(&format!("_{i:}"), Span::call_site()),
-                                                        subpat: None
-                                                    })).collect(),
-                                                })],
-                                                or2_token: single_token!(Or),
-                                                output: syn::ReturnType::Default,
-                                                body: Box::new(syn::Expr::Call(syn::ExprCall {
-                                                    attrs: vec![],
-                                                    func: Box::new(syn::Expr::Path(syn::ExprPath{
-                                                        attrs: vec![],
-                                                        qself: None,
-                                                        path: syn::Path {
-                                                            leading_colon: None,
-                                                            segments: punctuate![
-                                                                syn::PathSegment {
-                                                                    ident: ident!(Self),
-                                                                    arguments: syn::PathArguments::None
-                                                                },
-                                                                syn::PathSegment {
-                                                                    ident: v.ident,
-                                                                    arguments: syn::PathArguments::None
-                                                                },
-                                                            ],
-                                                        },
-                                                    })),
-                                                    paren_token: delim_token!(Paren),
-                                                    args: {
-                                                        if fields.len() == 1 { // FIXME: syn bug, again, again, ...
-                                                            punctuate![syn::Expr::MethodCall(syn::ExprMethodCall {
-                                                                attrs: vec![],
-                                                                receiver: Box::new(syn::Expr::Field(syn::ExprField {
-                                                                    attrs: vec![],
-                                                                    base: Box::new(syn::Expr::Path(syn::ExprPath {
-                                                                        attrs: vec![],
-                                                                        qself: None,
-                                                                        path: syn::Path {
-                                                                            leading_colon: None,
-                                                                            segments: punctuate![syn::PathSegment {
-                                                                                ident: syn::Ident::new("_0", Span::call_site()),
-                                                                                arguments: syn::PathArguments::None,
-                                                                            }],
-                                                                        },
-                                                                    })),
-                                                                    dot_token: single_token!(Dot),
-                                                                    member: syn::Member::Unnamed(syn::Index {
-                                                                        index: 0,
-                                                                        span: Span::call_site(),
-                                                                    }),
-                                                                })),
-                                                                dot_token: single_token!(Dot),
-                                                                method: syn::Ident::new("clone", Span::call_site()),
-                                                                turbofish: None,
-                                                                paren_token: delim_token!(Paren),
-                                                                args: punctuate![],
-                                                            })]
-                                                        } else {
-                                                            (0..fields.len()).map(|i| {
-                                                                syn::Expr::MethodCall(syn::ExprMethodCall {
-                                                                    attrs: vec![],
-                                                                    receiver: Box::new(syn::Expr::Path(syn::ExprPath {
-                                                                        attrs: vec![],
-                                                                        qself: None,
-                                                                        path: syn::Path {
-                                                                            leading_colon: None,
-                                                                            segments: punctuate![syn::PathSegment {
-                                                                                ident: syn::Ident::new(&format!("_{i:}"), Span::call_site()),
-                                                                                arguments: syn::PathArguments::None,
-                                                                            }],
-                                                                        },
-                                                                    })),
-                                                                    dot_token: single_token!(Dot),
-                                                                    method: syn::Ident::new("clone", Span::call_site()),
-                                                                    turbofish: None,
-                                                                    paren_token: delim_token!(Paren),
-                                                                    args: punctuate![],
-                                                                })
-                                                            })
-                                                            .collect()
-                                                        }
-                                                    },
-                                                })),
-                                            })],
-                                        })],
-                                    })),
-                                    comma: Some(single_token!(Comma)),
-                                },
-                                syn::Fields::Unit => syn::Arm {
-                                    attrs: vec![],
-                                    pat: syn::Pat::Path(syn::ExprPath {
-                                        attrs: vec![],
-                                        qself: None,
-                                        path: syn::Path {
-                                            leading_colon: None,
-                                            segments: punctuate![
-                                                syn::PathSegment {
-                                                    ident: ident!(Self),
-                                                    arguments: syn::PathArguments::None
-                                                },
-                                                syn::PathSegment {
-                                                    ident: v.ident,
-                                                    arguments: syn::PathArguments::None
-                                                }
-                                            ],
+                            pat: syn::Pat::Wild(syn::PatWild {
+                                attrs: vec![],
+                                underscore_token: syn::parse2(quote! { _ })?,
+                            }),
+                            guard: None,
+                            fat_arrow_token: syn::parse2(quote! { => })?,
+                            body: Box::new(one_of(
+                                &d.variants,
+                                usize::MAX,
+                                false,
+                                ident,
+                                container,
+                            )?),
+                            comma: Some(syn::parse2(quote! { , })?),
+                        });
+                        arms
+                    },
+                }),
+                None,
+            )])?,
+            make_shrink_fn(shrink_body(
+                syn::Expr::Match(syn::ExprMatch {
+                    attrs: vec![],
+                    match_token: syn::parse2(quote! { match })?,
+                    expr: Box::new(syn::parse2(quote! { self })?),
+                    brace_token: delim_token!(Brace),
+                    arms: simpler_variants(&d.variants)?
+                        .into_iter()
+                        .zip(d.variants)
+                        .map(|(simpler, v)| {
+                            if attrs::variant(&v.attrs)?.skip {
+                                let variant = v.ident;
+                                return syn::parse2(quote! {
+                                    Self::#variant { .. } => ::quickcheck::empty_shrinker(),
+                                });
+                            }
+                            shrink_arm(
+                                &syn::Path {
+                                    leading_colon: None,
+                                    segments: punctuate![
+                                        syn::PathSegment {
+                                            ident: ident!(Self),
+                                            arguments: syn::PathArguments::None
                                         },
-                                    }),
-                                    guard: None,
-                                    fat_arrow_token: syn::token::FatArrow {
-                                        spans: [Span::call_site(), Span::call_site()],
-                                    },
-                                    body: Box::new(syn::Expr::Call(syn::ExprCall {
-                                        attrs: vec![],
-                                        func: Box::new(syn::Expr::Path(syn::ExprPath {
-                                            attrs: vec![],
-                                            qself: None,
-                                            path: syn::Path {
-                                                leading_colon: Some(syn::token::PathSep {
-                                                    spans: [Span::call_site(), Span::call_site()],
-                                                }),
-                                                segments: punctuate![
-                                                    syn::PathSegment {
-                                                        ident: ident!(quickcheck),
-                                                        arguments: syn::PathArguments::None
-                                                    },
-                                                    syn::PathSegment {
-                                                        ident: ident!(empty_shrinker),
-                                                        arguments: syn::PathArguments::None
-                                                    }
-                                                ],
-                                            },
-                                        })),
-                                        paren_token: delim_token!(Paren),
-                                        args: punctuate![],
-                                    })),
-                                    comma: Some(single_token!(Comma)),
+                                        syn::PathSegment {
+                                            ident: v.ident,
+                                            arguments: syn::PathArguments::None
+                                        }
+                                    ],
                                 },
-                            })
-                            .collect(),
-                    }),
-                    None,
-                )])?,
-            ],
+                                &v.fields,
+                                &self_ty,
+                                &simpler,
+                            )
+                        })
+                        .collect::<syn::Result<_>>()?,
+                }),
+                container,
+            )?)?,
+        ],
     })
 }
 
 /// Implement for a `struct`.
 fn from_struct(
     attrs: Vec<syn::Attribute>,
-    ident: syn::Ident,
-    generics: syn::Generics,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
     d: syn::DataStruct,
+    container: &attrs::Container,
 ) -> syn::Result<syn::ItemImpl> {
+    let self_ty = make_self_ty(ident.clone(), generics.clone());
+    let fields: Vec<_> = d.fields.iter().collect();
+    let constrained = constrain_generics(generics, container, &fields, ident, &self_ty)?;
+    let self_path = syn::Path {
+        leading_colon: None,
+        segments: punctuate![syn::PathSegment {
+            ident: ident!(Self),
+            arguments: syn::PathArguments::None
+        }],
+    };
+    let shrink = shrink_arm(&self_path, &d.fields, &self_ty, &[])?;
     Ok(syn::ItemImpl {
         attrs,
         defaultness: None,
         unsafety: None,
         impl_token: syn::parse2(quote! { impl })?,
-        generics: constrain_generics(&generics),
+        generics: constrained,
         trait_: Some((
             None,
             syn::parse2(quote! { ::quickcheck::Arbitrary })?,
             syn::parse2(quote! { for })?,
         )),
-        self_ty: Box::new(make_self_ty(ident, generics)),
+        self_ty: Box::new(self_ty),
         brace_token: delim_token!(Brace),
-        items: vec![make_arbitrary_fn(vec![syn::Stmt::Expr(
-            all_of(
-                syn::Path {
-                    leading_colon: None,
-                    segments: punctuate![syn::PathSegment {
-                        ident: ident!(Self),
-                        arguments: syn::PathArguments::None
-                    }],
-                },
-                d.fields,
-            ),
-            None,
-        )])?],
+        items: vec![
+            make_arbitrary_fn(vec![syn::Stmt::Expr(
+                all_of(self_path, d.fields, ident, container)?,
+                None,
+            )])?,
+            make_shrink_fn(shrink_body(
+                syn::Expr::Match(syn::ExprMatch {
+                    attrs: vec![],
+                    match_token: syn::parse2(quote! { match })?,
+                    expr: Box::new(syn::parse2(quote! { self })?),
+                    brace_token: delim_token!(Brace),
+                    arms: vec![shrink],
+                }),
+                container,
+            )?)?,
+        ],
     })
 }
 
 /// Implement for a `union`.
 fn from_union(
     attrs: Vec<syn::Attribute>,
-    ident: syn::Ident,
-    generics: syn::Generics,
-    _: syn::DataUnion,
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    d: &syn::DataUnion,
+    container: &attrs::Container,
 ) -> syn::Result<syn::ItemImpl> {
+    let mut members = vec![];
+    for f in &d.fields.named {
+        let (member, rest) = attrs::member(&f.attrs)?;
+        if member.active || member.weight.is_some() {
+            let field = syn::Field {
+                attrs: rest,
+                ..f.clone()
+            };
+            members.push((member, field));
+        }
+    }
+    let active = members.iter().filter(|&&(ref m, _)| m.active).count();
+    if members.is_empty() {
+        bail!(
+            d.union_token.span,
+            "A `union` needs `#[quickcheck(active)]` on the field to initialize, or `#[quickcheck(weight = n)]` on each field it may initialize"
+        )
+    }
+    if active > 0 && members.len() > 1 {
+        bail!(
+            d.union_token.span,
+            "Only one field of a `union` can be `active`, and then no field can take a `weight`"
+        )
+    }
+    let self_ty = make_self_ty(ident.clone(), generics.clone());
+    let fields: Vec<_> = members.iter().map(|&(_, ref f)| f).collect();
+    let constrained = constrain_generics(generics, container, &fields, ident, &self_ty)?;
+    let mut ones = vec![];
+    for &(_, ref f) in &members {
+        ones.push(all_of(
+            syn::parse2(quote! { Self })?,
+            syn::Fields::Named(syn::FieldsNamed {
+                brace_token: d.fields.brace_token,
+                named: punctuate![f.clone()],
+            }),
+            ident,
+            container,
+        )?);
+    }
+    let arbitrary = if let [ref one] = *ones.as_slice() {
+        one.clone()
+    } else {
+        weighted(
+            members.iter().map(|&(ref m, _)| m.weight.unwrap_or(1)),
+            ones.iter()
+                .map(|one| {
+                    syn::parse2::<syn::Expr>(
+                        quote! { |g: &mut ::quickcheck::Gen| -> Self { #one } },
+                    )
+                })
+                .collect::<syn::Result<_>>()?,
+            d.union_token.span,
+        )?
+    };
+    // Only the field we wrote is safe to read, and nothing records which one that was, so `union`s don't shrink
+    let shrink = syn::parse2(quote! { ::quickcheck::empty_shrinker() })?;
     Ok(syn::ItemImpl {
         attrs,
         defaultness: None,
         unsafety: None,
         impl_token: syn::parse2(quote! { impl })?,
-        generics: constrain_generics(&generics),
+        generics: constrained,
         trait_: Some((
             None,
             syn::parse2(quote! { ::quickcheck::Arbitrary })?,
             syn::parse2(quote! { for })?,
         )),
-        self_ty: Box::new(make_self_ty(ident, generics)),
+        self_ty: Box::new(self_ty),
         brace_token: delim_token!(Brace),
-        items: vec![make_arbitrary_fn(vec![syn::Stmt::Macro(syn::StmtMacro {
-            attrs: vec![],
-            mac: syn::parse2(
-                quote! { todo!("`union`s not yet implemented in ``derive-quickcheck`") },
-            )?,
-            semi_token: None,
-        })])?],
+        items: vec![
+            make_arbitrary_fn(vec![syn::Stmt::Expr(arbitrary, None)])?,
+            make_shrink_fn(shrink_body(shrink, container)?)?,
+        ],
     })
 }
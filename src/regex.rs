@@ -0,0 +1,270 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Generate and recognize strings matching a `#[quickcheck(regex = "...")]` pattern.
+//! The pattern is parsed once, at compile time, and turned into plain Rust, so the user's crate doesn't need `regex` at all.
+
+use quote::quote;
+use regex_syntax::hir::{Class, Hir, HirKind, Look};
+
+/// Strings matching `pattern` from start to end, shrinking to shorter (or simpler) strings that still match.
+pub fn strings(ty: &syn::Type, pattern: &syn::LitStr) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let hir = regex_syntax::parse(&pattern.value())
+        .map_err(|e| syn::Error::new_spanned(pattern, format!("Invalid regex: {e}")))?;
+    let generate = generate(&hir, pattern)?;
+    let ends = ends(&hir, quote! { at }, pattern)?;
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                let mut out = String::new();
+                #generate
+                out
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                Box::new(<#ty as ::quickcheck::Arbitrary>::shrink(x).filter(|s| {
+                    let chars: Vec<char> = s.chars().collect();
+                    let at: Vec<usize> = vec![0];
+                    (#ends).contains(&chars.len())
+                }))
+            }
+        })?,
+    ))
+}
+
+/// Unicode ranges of a character class, as inclusive `(lo, hi)` pairs.
+fn ranges(class: &Class, pattern: &syn::LitStr) -> syn::Result<Vec<(char, char)>> {
+    let Class::Unicode(ref unicode) = *class else {
+        return Err(syn::Error::new_spanned(
+            pattern,
+            "`regex` generates `String`s, so it can't match arbitrary bytes",
+        ));
+    };
+    // `char` ranges skip the surrogates implicitly, but `u32` arithmetic wouldn't, so split around them
+    let out: Vec<_> = unicode
+        .iter()
+        .flat_map(|r| {
+            if r.start() < '\u{e000}' && r.end() > '\u{d7ff}' {
+                vec![(r.start(), '\u{d7ff}'), ('\u{e000}', r.end())]
+            } else {
+                vec![(r.start(), r.end())]
+            }
+        })
+        .collect();
+    if out.is_empty() {
+        return Err(syn::Error::new_spanned(
+            pattern,
+            "This regex contains an empty character class, so nothing can match it",
+        ));
+    }
+    Ok(out)
+}
+
+/// A literal's bytes as a `String`.
+fn literal(lit: &regex_syntax::hir::Literal, pattern: &syn::LitStr) -> syn::Result<String> {
+    String::from_utf8(lit.0.to_vec())
+        .map_err(|e| syn::Error::new_spanned(pattern, format!("Regex literal isn't UTF-8: {e}")))
+}
+
+/// Only `^` and `$` (outside multi-line mode) make sense for a whole string.
+fn check_look(look: Look, pattern: &syn::LitStr) -> syn::Result<()> {
+    if matches!(look, Look::Start | Look::End) {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(
+            pattern,
+            "`regex` supports `^` and `$`, but not word boundaries or multi-line anchors",
+        ))
+    }
+}
+
+/// Statements that append a random match of `hir` to `out`.
+/// Bounded repetitions use their own bounds; unbounded ones repeat up to `g.size()` extra times.
+fn generate(hir: &Hir, pattern: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(match *hir.kind() {
+        HirKind::Empty => quote! {},
+        HirKind::Literal(ref lit) => {
+            let s = literal(lit, pattern)?;
+            quote! { out.push_str(#s); }
+        }
+        HirKind::Class(ref class) => {
+            let ranges = ranges(class, pattern)?;
+            let total = ranges
+                .iter()
+                .map(|&(lo, hi)| {
+                    u64::from(u32::from(hi).saturating_sub(u32::from(lo))).saturating_add(1)
+                })
+                .fold(0, u64::saturating_add);
+            let draw = crate::uniform(&quote! { g });
+            let (lo, hi): (Vec<_>, Vec<_>) = ranges
+                .into_iter()
+                .map(|(lo, hi)| (u32::from(lo), u32::from(hi)))
+                .unzip();
+            quote! {
+                let mut pick = (#draw).wrapping_rem(#total);
+                for (lo, hi) in [#((#lo, #hi)),*] {
+                    let width = u64::from(hi - lo);
+                    if pick <= width {
+                        out.extend(u32::try_from(pick).ok().and_then(|offset| char::from_u32(lo + offset)));
+                        break;
+                    }
+                    pick -= width + 1;
+                }
+            }
+        }
+        HirKind::Look(look) => {
+            check_look(look, pattern)?;
+            quote! {}
+        }
+        HirKind::Repetition(ref rep) => {
+            let sub = generate(&rep.sub, pattern)?;
+            let min = usize::try_from(rep.min).unwrap_or(usize::MAX);
+            let max = rep.max.map_or_else(
+                || quote! { #min + g.size() },
+                |bound| {
+                    let reps = usize::try_from(bound).unwrap_or(usize::MAX);
+                    quote! { #reps }
+                },
+            );
+            quote! {
+                let extra = <usize as ::quickcheck::Arbitrary>::arbitrary(g);
+                let reps = #min + (#max - #min).checked_add(1).map_or(extra, |n| extra.wrapping_rem(n));
+                for _ in 0..reps {
+                    #sub
+                }
+            }
+        }
+        HirKind::Capture(ref capture) => generate(&capture.sub, pattern)?,
+        HirKind::Concat(ref subs) => {
+            let each = subs
+                .iter()
+                .map(|sub| generate(sub, pattern))
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! { #({ #each })* }
+        }
+        HirKind::Alternation(ref subs) => {
+            let n = u64::try_from(subs.len()).unwrap_or(u64::MAX);
+            let mut branches = subs
+                .iter()
+                .map(|sub| generate(sub, pattern))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let last = branches.pop();
+            let i = 0..n;
+            let draw = crate::uniform(&quote! { g });
+            quote! {
+                match (#draw).wrapping_rem(#n) {
+                    #(#i => { #branches })*
+                    _ => { #last }
+                }
+            }
+        }
+    })
+}
+
+/// An expression that, given `at` (the sorted, deduplicated positions in `chars` where `hir` may start),
+/// evaluates to the sorted, deduplicated positions where a match of `hir` may end.
+fn ends(
+    hir: &Hir,
+    at: proc_macro2::TokenStream,
+    pattern: &syn::LitStr,
+) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(match *hir.kind() {
+        HirKind::Empty => at,
+        HirKind::Literal(ref lit) => {
+            let s = literal(lit, pattern)?;
+            let cs = s.chars();
+            let n = s.chars().count();
+            quote! {
+                (#at).into_iter()
+                    .filter(|&p| chars.get(p..).is_some_and(|rest| rest.starts_with(&[#(#cs),*])))
+                    .map(|p| p + #n)
+                    .collect::<Vec<usize>>()
+            }
+        }
+        HirKind::Class(ref class) => {
+            let (lo, hi): (Vec<_>, Vec<_>) = ranges(class, pattern)?.into_iter().unzip();
+            quote! {
+                (#at).into_iter()
+                    .filter(|&p| chars.get(p).is_some_and(|c| matches!(*c, #(#lo..=#hi)|*)))
+                    .map(|p| p + 1)
+                    .collect::<Vec<usize>>()
+            }
+        }
+        HirKind::Look(look) => {
+            check_look(look, pattern)?;
+            if matches!(look, Look::Start) {
+                quote! { (#at).into_iter().filter(|&p| p == 0).collect::<Vec<usize>>() }
+            } else {
+                quote! { (#at).into_iter().filter(|&p| p == chars.len()).collect::<Vec<usize>>() }
+            }
+        }
+        HirKind::Repetition(ref rep) => {
+            // Breadth-first: each round only continues from positions we haven't reached in fewer repetitions.
+            let min = ends(&rep.sub, quote! { at }, pattern)?;
+            let more = ends(&rep.sub, quote! { frontier }, pattern)?;
+            let rounds = rep.max.map_or_else(
+                || quote! { usize::MAX },
+                |max| {
+                    let extra = usize::try_from(max.saturating_sub(rep.min)).unwrap_or(usize::MAX);
+                    quote! { #extra }
+                },
+            );
+            let reps = usize::try_from(rep.min).unwrap_or(usize::MAX);
+            let required = (reps > 0).then(|| {
+                quote! {
+                    for _ in 0..#reps {
+                        at = #min;
+                    }
+                }
+            });
+            quote! {{
+                let mut at: Vec<usize> = #at;
+                #required
+                let mut all = at.clone();
+                let mut frontier = at;
+                for _ in 0..#rounds {
+                    frontier = #more;
+                    frontier.retain(|p| !all.contains(p));
+                    if frontier.is_empty() {
+                        break;
+                    }
+                    all.extend_from_slice(&frontier);
+                }
+                all.sort_unstable();
+                all.dedup();
+                all
+            }}
+        }
+        HirKind::Capture(ref capture) => ends(&capture.sub, at, pattern)?,
+        HirKind::Concat(ref subs) => {
+            let mut out = at;
+            for sub in subs {
+                out = ends(sub, quote! { at }, pattern).map(|next| {
+                    quote! {{
+                        let at: Vec<usize> = #out;
+                        #next
+                    }}
+                })?;
+            }
+            out
+        }
+        HirKind::Alternation(ref subs) => {
+            let branches = subs
+                .iter()
+                .map(|sub| ends(sub, quote! { at.clone() }, pattern))
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {{
+                let at: Vec<usize> = #at;
+                let mut all: Vec<usize> = vec![];
+                #(all.extend(#branches);)*
+                all.sort_unstable();
+                all.dedup();
+                all
+            }}
+        }
+    })
+}
@@ -0,0 +1,811 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+//! Decide how to generate and shrink one value from its type and its `#[quickcheck(...)]` attributes.
+
+use quote::quote;
+
+/// Standard collections other than `String`, whose elements we know how to name.
+const COLLECTIONS: [&str; 8] = [
+    "Vec",
+    "VecDeque",
+    "LinkedList",
+    "BinaryHeap",
+    "HashSet",
+    "BTreeSet",
+    "HashMap",
+    "BTreeMap",
+];
+
+/// A pair of expressions that generate and shrink values of one type.
+pub struct Strategy {
+    /// Callable as `fn(&mut ::quickcheck::Gen) -> T`.
+    pub arbitrary: syn::Expr,
+    /// Callable as `fn(&T) -> Box<dyn Iterator<Item = T>>`, or `None` to hold the value fixed.
+    pub shrink: Option<syn::Expr>,
+}
+
+/// Call a strategy's expression, parenthesizing it if it's anything but a plain path.
+pub fn call(f: &syn::Expr, arg: &proc_macro2::TokenStream) -> syn::Result<syn::Expr> {
+    syn::parse2(if matches!(*f, syn::Expr::Path(_)) {
+        quote! { #f(#arg) }
+    } else {
+        quote! { (#f)(#arg) }
+    })
+}
+
+/// Choose a strategy for a value of type `ty` with these attributes.
+pub fn new(ty: &syn::Type, mut attrs: crate::attrs::Field) -> syn::Result<Strategy> {
+    if attrs.skip {
+        return Ok(Strategy {
+            arbitrary: syn::parse2(quote! {
+                |_: &mut ::quickcheck::Gen| -> #ty { ::core::default::Default::default() }
+            })?,
+            shrink: None,
+        });
+    }
+    if is_marker(ty) && attrs.with.is_none() {
+        if attrs.shrink_with.is_some()
+            || attrs.no_shrink
+            || attrs.range.is_some()
+            || attrs.regex.is_some()
+            || attrs.finite
+            || attrs.has_len()
+            || attrs.has_elements()
+            || attrs.has_size()
+        {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "`PhantomData` and `PhantomPinned` have only one value, so they can't take any field attributes but `with` or `skip`",
+            ));
+        }
+        return Ok(Strategy {
+            arbitrary: syn::parse2(quote! {
+                |_: &mut ::quickcheck::Gen| -> #ty { ::core::default::Default::default() }
+            })?,
+            shrink: None,
+        });
+    }
+    // Otherwise `collection` would fail inside the generated code, e.g. asking `Option` for `FromIterator`
+    if attrs.has_len() && !is_string(ty) && !is_collection(ty) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "`len`, `min_len`, `max_len`, and `non_empty` need a `String` or a standard collection like `Vec` or `HashMap`",
+        ));
+    }
+    let (arbitrary, shrink) = if let Some(ref range) = attrs.range {
+        if is_float(ty, range, attrs.finite) {
+            float_range(ty, range)?
+        } else {
+            int_range(ty, range)?
+        }
+    } else if let Some(ref pattern) = attrs.regex {
+        if !is_string(ty) {
+            return Err(syn::Error::new_spanned(ty, "`regex` needs a `String`"));
+        }
+        crate::regex::strings(ty, pattern)?
+    } else if attrs.finite {
+        (
+            syn::parse2(quote! {
+                |g: &mut ::quickcheck::Gen| -> #ty {
+                    loop {
+                        let x = <#ty as ::quickcheck::Arbitrary>::arbitrary(g);
+                        if x.is_finite() {
+                            break x;
+                        }
+                    }
+                }
+            })?,
+            syn::parse2(quote! { <#ty as ::quickcheck::Arbitrary>::shrink })?,
+        )
+    } else if attrs.each.is_some() && is_option(ty) {
+        let each = attrs
+            .each
+            .take()
+            .map_or_else(Default::default, |each| *each);
+        option(ty, new(&element_type(ty)?, each)?)?
+    } else if attrs.has_len() || attrs.has_elements() {
+        let (min, max) = len_bounds(&attrs);
+        let elem = element(ty, &mut attrs)?;
+        collection(ty, &min, max.as_ref(), elem, None)?
+    } else if is_str_ref(ty) || is_cow_str(ty) {
+        text(ty)?
+    } else {
+        (
+            syn::parse2(quote! { <#ty as ::quickcheck::Arbitrary>::arbitrary })?,
+            syn::parse2(quote! { <#ty as ::quickcheck::Arbitrary>::shrink })?,
+        )
+    };
+    Ok(Strategy {
+        arbitrary: attrs
+            .with
+            .map_or(Ok(arbitrary), |with| syn::parse2(quote! { #with }))?,
+        shrink: if attrs.no_shrink {
+            None
+        } else if let Some(shrink_with) = attrs.shrink_with {
+            Some(syn::parse2(quote! { #shrink_with })?)
+        } else {
+            Some(shrink)
+        },
+    })
+}
+
+/// Generate a collection that splits `g`'s size between its elements, with its length and element attributes,
+/// so a rose tree like `Vec<Self>` has at most about `g.size()` nodes instead of blowing up exponentially,
+/// and a `Vec<Vec<u8>>` under `split_size` has about `g.size()` bytes instead of its square.
+/// A `recursive` collection has to be empty once the size runs out, or recursion might never end, so it can't have a minimum length.
+pub fn split_collection(
+    ty: &syn::Type,
+    mut attrs: crate::attrs::Field,
+    recursive: bool,
+) -> syn::Result<syn::Expr> {
+    /// An integer literal `0`, the only minimum length we can be sure of at compile time.
+    fn zero(n: &syn::Expr) -> bool {
+        matches!(
+            *n,
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(ref int),
+                ..
+            }) if int.base10_digits() == "0"
+        )
+    }
+    let least = match attrs.len {
+        Some(syn::Expr::Range(ref range)) => range.start.as_deref(),
+        Some(ref exact) => Some(exact),
+        None => attrs.min_len.as_ref(),
+    };
+    if recursive && (attrs.non_empty || least.is_some_and(|n| !zero(n))) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "A recursive collection has to be empty once the size runs out, so it can't have a minimum length",
+        ));
+    }
+    let (min, max) = len_bounds(&attrs);
+    let elem = element(ty, &mut attrs)?;
+    collection(ty, &min, max.as_ref(), elem, Some(recursive)).map(|(arbitrary, _)| arbitrary)
+}
+
+/// What `new` relies on to generate and shrink a value of type `ty` with these attributes:
+/// `Arbitrary` for each type it hands off to `quickcheck`, or `Default` for `skip`.
+pub fn bounds(
+    ty: &syn::Type,
+    attrs: &crate::attrs::Field,
+) -> syn::Result<Vec<syn::WherePredicate>> {
+    if attrs.skip {
+        return Ok(vec![syn::parse2(quote! { #ty: ::core::default::Default })?]);
+    }
+    if is_marker(ty) && attrs.with.is_none() {
+        return Ok(vec![]);
+    }
+    let generate = attrs.with.is_none();
+    let shrink = !attrs.no_shrink && attrs.shrink_with.is_none();
+    let arbitrary = if attrs.range.is_some() || attrs.regex.is_some() || !(generate || shrink) {
+        vec![]
+    } else if attrs.finite {
+        vec![ty.clone()]
+    } else if attrs.has_elements() {
+        let none = crate::attrs::Field::default();
+        if let (Some((k, v)), None) = (map_types(ty), attrs.each.as_ref()) {
+            let mut out = bounds(&k, attrs.keys.as_deref().unwrap_or(&none))?;
+            out.extend(bounds(&v, attrs.values.as_deref().unwrap_or(&none))?);
+            return Ok(out);
+        }
+        return bounds(&element_type(ty)?, attrs.each.as_deref().unwrap_or(&none));
+    } else if attrs.has_len() {
+        // Elements come from their own `arbitrary`, but shrinking goes through the whole collection's
+        let mut out = vec![];
+        if generate {
+            out.push(element_type(ty)?);
+        }
+        if shrink {
+            out.push(ty.clone());
+        }
+        out
+    } else if is_str_ref(ty) || is_cow_str(ty) {
+        vec![]
+    } else {
+        vec![ty.clone()]
+    };
+    arbitrary
+        .into_iter()
+        .map(|t| syn::parse2(quote! { #t: ::quickcheck::Arbitrary }))
+        .collect()
+}
+
+/// Whether a range attribute describes floating-point numbers: marked `finite`, typed `f32`/`f64`, or bounded by a float literal.
+fn is_float(ty: &syn::Type, range: &syn::ExprRange, finite: bool) -> bool {
+    /// Float literal, possibly negated.
+    fn float_lit(e: &syn::Expr) -> bool {
+        matches!(
+            *e,
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Float(_),
+                ..
+            })
+        ) || matches!(*e, syn::Expr::Unary(ref neg) if float_lit(&neg.expr))
+            || matches!(*e, syn::Expr::Paren(ref paren) if float_lit(&paren.expr))
+    }
+    finite
+        || matches!(*ty, syn::Type::Path(ref p) if p.path.is_ident("f32") || p.path.is_ident("f64"))
+        || range.start.as_deref().is_some_and(float_lit)
+        || range.end.as_deref().is_some_and(float_lit)
+}
+
+/// `String`s, leaked into `&'static str`s or wrapped in `Cow::Owned`, since `quickcheck` implements neither.
+/// Leaking costs a little memory for each value (and each shrink we try), which is fine for a test.
+fn text(ty: &syn::Type) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let wrap = if is_str_ref(ty) {
+        quote! { |s: String| -> #ty { Box::leak(s.into_boxed_str()) } }
+    } else {
+        quote! { |s: String| -> #ty { ::std::borrow::Cow::Owned(s) } }
+    };
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                (#wrap)(<String as ::quickcheck::Arbitrary>::arbitrary(g))
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                Box::new(<String as ::quickcheck::Arbitrary>::shrink(&x.to_string()).map(#wrap))
+            }
+        })?,
+    ))
+}
+
+/// Inclusive bounds `(lo, hi)` of an integer range, defaulting to the type's own bounds.
+fn int_bounds(
+    ty: &syn::Type,
+    range: &syn::ExprRange,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let lo = range
+        .start
+        .as_ref()
+        .map_or_else(|| quote! { <#ty>::MIN }, |start| quote! { #start });
+    let hi = range.end.as_ref().map_or_else(
+        || quote! { <#ty>::MAX },
+        |end| match range.limits {
+            syn::RangeLimits::Closed(_) => quote! { #end },
+            syn::RangeLimits::HalfOpen(_) => quote! { (#end) - 1 },
+        },
+    );
+    (lo, hi)
+}
+
+/// Uniform integers in a range (with a bias toward its bounds), shrinking toward the lower bound.
+/// All arithmetic wraps in `i128`/`u128`, which is exact for every primitive integer type.
+fn int_range(ty: &syn::Type, range: &syn::ExprRange) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let (lo, hi) = int_bounds(ty, range);
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                let lo: #ty = #lo;
+                let hi: #ty = #hi;
+                assert!(lo <= hi, "`derive-quickcheck`: empty range");
+                if <u8 as ::quickcheck::Arbitrary>::arbitrary(g).wrapping_rem(10) == 0 {
+                    return *g.choose(&[lo, hi]).unwrap();
+                }
+                let span = (hi as i128).wrapping_sub(lo as i128) as u128;
+                let offset = <u128 as ::quickcheck::Arbitrary>::arbitrary(g);
+                let offset = if span == u128::MAX { offset } else { offset.wrapping_rem(span + 1) };
+                (lo as i128).wrapping_add(offset as i128) as #ty
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                let lo: #ty = #lo;
+                let hi: #ty = #hi;
+                if *x < lo || *x > hi {
+                    return ::quickcheck::empty_shrinker();
+                }
+                let offset = (*x as i128).wrapping_sub(lo as i128) as u128;
+                Box::new(
+                    <u128 as ::quickcheck::Arbitrary>::shrink(&offset)
+                        .map(move |o| (lo as i128).wrapping_add(o as i128) as #ty),
+                )
+            }
+        })?,
+    ))
+}
+
+/// Uniform finite floats in a range, shrinking toward the lower bound by halving the distance to it.
+fn float_range(ty: &syn::Type, range: &syn::ExprRange) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let (Some(lo), Some(hi)) = (range.start.as_ref(), range.end.as_ref()) else {
+        return Err(syn::Error::new_spanned(
+            range,
+            "Floating-point ranges need both bounds",
+        ));
+    };
+    let in_range = match range.limits {
+        syn::RangeLimits::HalfOpen(_) => quote! { x < hi },
+        syn::RangeLimits::Closed(_) => quote! { x <= hi },
+    };
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                let lo: #ty = #lo;
+                let hi: #ty = #hi;
+                assert!(lo.is_finite() && hi.is_finite() && lo <= hi, "`derive-quickcheck`: invalid range");
+                // 53 random bits: uniform in [0, 1)
+                #[allow(clippy::float_arithmetic)]
+                let unit = (<u64 as ::quickcheck::Arbitrary>::arbitrary(g) >> 11) as f64
+                    / 9_007_199_254_740_992.0;
+                // Interpolate rather than scale `hi - lo`, which overflows to infinity for ranges wider than the type's `MAX`
+                #[allow(clippy::float_arithmetic, clippy::suboptimal_flops)]
+                let x = (lo as f64 * (1.0 - unit) + hi as f64 * unit) as #ty;
+                if #in_range { x } else { lo }
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                let lo: #ty = #lo;
+                let x = *x;
+                if !(x > lo) || !x.is_finite() {
+                    return ::quickcheck::empty_shrinker();
+                }
+                // Halve before subtracting, for the same reason
+                #[allow(clippy::float_arithmetic)]
+                let halves = ::core::iter::successors(Some(x / 2.0 - lo / 2.0), |d| Some(d / 2.0))
+                    .take_while(|d| *d > 0.0)
+                    .map(move |d| x - d);
+                Box::new(
+                    ::core::iter::once(lo)
+                        .chain(halves)
+                        .filter(move |c| *c >= lo && *c < x),
+                )
+            }
+        })?,
+    ))
+}
+
+/// Inclusive `(min, max)` length bounds from `len`, `min_len`, `max_len`, and `non_empty`, where `None` means "up to `g.size()`".
+fn len_bounds(
+    attrs: &crate::attrs::Field,
+) -> (proc_macro2::TokenStream, Option<proc_macro2::TokenStream>) {
+    let (lo, hi) = match attrs.len {
+        Some(syn::Expr::Range(ref range)) => (
+            range.start.as_ref().map(|start| quote! { #start }),
+            range.end.as_ref().map(|end| match range.limits {
+                syn::RangeLimits::Closed(_) => quote! { #end },
+                syn::RangeLimits::HalfOpen(_) => quote! { (#end) - 1 },
+            }),
+        ),
+        Some(ref exact) => (Some(quote! { #exact }), Some(quote! { #exact })),
+        None => (
+            attrs.min_len.as_ref().map(|n| quote! { #n }),
+            attrs.max_len.as_ref().map(|n| quote! { #n }),
+        ),
+    };
+    let min = match lo {
+        Some(n) if attrs.non_empty => quote! { ::core::cmp::max(#n, 1) },
+        Some(n) => n,
+        None if attrs.non_empty => quote! { 1 },
+        None => quote! { 0 },
+    };
+    (min, hi)
+}
+
+/// Last path segment of a type, if it's a plain path.
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    if let syn::Type::Path(ref p) = *ty {
+        if p.qself.is_none() {
+            return p.path.segments.last();
+        }
+    }
+    None
+}
+
+/// Whether a type mentions `Self` or `name` anywhere, e.g. `Option<Box<Self>>` or `Vec<Tree<T>>`.
+pub fn mentions(ty: &syn::Type, name: &syn::Ident) -> bool {
+    search(quote! { #ty }, &|ident| ident == "Self" || ident == name)
+}
+
+/// Whether a type mentions any of these identifiers anywhere, e.g. `T` in `Vec<T::Item>`.
+pub fn mentions_any(ty: &syn::Type, idents: &[&syn::Ident]) -> bool {
+    search(quote! { #ty }, &|ident| idents.contains(&ident))
+}
+
+/// Whether any identifier in these tokens (including inside nested groups) matches.
+fn search(tokens: proc_macro2::TokenStream, hit: &dyn Fn(&syn::Ident) -> bool) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => hit(&ident),
+        proc_macro2::TokenTree::Group(group) => search(group.stream(), hit),
+        proc_macro2::TokenTree::Punct(_) | proc_macro2::TokenTree::Literal(_) => false,
+    })
+}
+
+/// Whether a type is a zero-sized marker like `PhantomData<T>` or `PhantomPinned`, which has only one value.
+fn is_marker(ty: &syn::Type) -> bool {
+    last_segment(ty)
+        .is_some_and(|last| last.ident == "PhantomData" || last.ident == "PhantomPinned")
+}
+
+/// Whether a type is spelled `&str`, with any lifetime (which will be `'static` by the time it's compiled).
+fn is_str_ref(ty: &syn::Type) -> bool {
+    matches!(
+        *ty,
+        syn::Type::Reference(ref r)
+            if r.mutability.is_none() && matches!(*r.elem, syn::Type::Path(ref p) if p.path.is_ident("str"))
+    )
+}
+
+/// Whether a type is spelled `Cow<str>`, with any lifetime.
+fn is_cow_str(ty: &syn::Type) -> bool {
+    last_segment(ty).is_some_and(|last| last.ident == "Cow")
+        && type_args(ty)
+            .first()
+            .is_some_and(|&arg| matches!(*arg, syn::Type::Path(ref p) if p.path.is_ident("str")))
+}
+
+/// Whether a type is spelled `String` (so its length counts `char`s, not bytes).
+fn is_string(ty: &syn::Type) -> bool {
+    last_segment(ty).is_some_and(|last| last.ident == "String")
+}
+
+/// Type arguments of a type's last path segment, e.g. `[K, V]` for `HashMap<K, V>`.
+pub fn type_args(ty: &syn::Type) -> Vec<&syn::Type> {
+    last_segment(ty).map_or_else(Vec::new, |last| {
+        if let syn::PathArguments::AngleBracketed(ref args) = last.arguments {
+            args.args
+                .iter()
+                .filter_map(|arg| {
+                    if let syn::GenericArgument::Type(ref t) = *arg {
+                        Some(t)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        } else {
+            vec![]
+        }
+    })
+}
+
+/// Whether a type is spelled `Option<T>`.
+pub fn is_option(ty: &syn::Type) -> bool {
+    last_segment(ty).is_some_and(|last| last.ident == "Option") && type_args(ty).len() == 1
+}
+
+/// Key and value types of a standard map, if we recognize it.
+fn map_types(ty: &syn::Type) -> Option<(syn::Type, syn::Type)> {
+    let last = last_segment(ty)?;
+    if last.ident != "HashMap" && last.ident != "BTreeMap" {
+        return None;
+    }
+    match *type_args(ty).as_slice() {
+        [k, v, ..] => Some((k.clone(), v.clone())),
+        _ => None,
+    }
+}
+
+/// Whether a type is one of the standard collections (but not `String` or `Option`).
+pub fn is_collection(ty: &syn::Type) -> bool {
+    last_segment(ty).is_some_and(|last| COLLECTIONS.iter().any(|name| last.ident == name))
+        && !type_args(ty).is_empty()
+}
+
+/// Type of one element of a standard collection (or `Option`), or `<C as IntoIterator>::Item` if we don't recognize it.
+pub fn element_type(ty: &syn::Type) -> syn::Result<syn::Type> {
+    if is_string(ty) {
+        return syn::parse2(quote! { char });
+    }
+    if let Some((k, v)) = map_types(ty) {
+        return syn::parse2(quote! { (#k, #v) });
+    }
+    if let Some(&elem) = type_args(ty).first() {
+        if is_collection(ty) || is_option(ty) {
+            return Ok(elem.clone());
+        }
+    }
+    syn::parse2(quote! { <#ty as ::core::iter::IntoIterator>::Item })
+}
+
+/// Whether a type is spelled exactly `Self` or like the type we're implementing for, e.g. `Tree<T>`.
+fn is_self(ty: &syn::Type, self_ty: &syn::Type) -> bool {
+    /// Generic arguments, spelled out, so `Tree<T>` and `Tree<U>` differ but `List` and `List<>` don't.
+    fn args(last: &syn::PathSegment) -> Vec<String> {
+        if let syn::PathArguments::AngleBracketed(ref bracketed) = last.arguments {
+            bracketed
+                .args
+                .iter()
+                .map(|arg| quote! { #arg }.to_string())
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+    match (last_segment(ty), last_segment(self_ty)) {
+        (Some(last), Some(own)) => {
+            (last.ident == "Self" && last.arguments.is_none())
+                || (last.ident == own.ident && args(last) == args(own))
+        }
+        _ => false,
+    }
+}
+
+/// Direct subterms of type `Self` in a field bound (by reference) to `binding`, as an iterator,
+/// if the field is a `Box<Self>`, an `Option<Box<Self>>`, or a standard collection of `Self`.
+pub fn subterms(
+    ty: &syn::Type,
+    self_ty: &syn::Type,
+    binding: &syn::Ident,
+) -> Option<proc_macro2::TokenStream> {
+    let &inner = type_args(ty).first()?;
+    let boxed = |t: &syn::Type| {
+        last_segment(t).is_some_and(|last| last.ident == "Box")
+            && type_args(t)
+                .first()
+                .is_some_and(|&boxed| is_self(boxed, self_ty))
+    };
+    if boxed(ty) {
+        Some(quote! { ::core::iter::once((**#binding).clone()) })
+    } else if is_option(ty) && boxed(inner) {
+        Some(quote! { #binding.as_deref().cloned().into_iter() })
+    } else if is_collection(ty) && map_types(ty).is_none() && is_self(inner, self_ty) {
+        Some(quote! { #binding.clone().into_iter() })
+    } else {
+        None
+    }
+}
+
+/// Strategy for each element of a collection from `each(...)`, or for each entry of a map from `keys(...)` and `values(...)`.
+/// `None` if neither applies, so elements fall back to their own `Arbitrary` implementation.
+fn element(ty: &syn::Type, attrs: &mut crate::attrs::Field) -> syn::Result<Option<Strategy>> {
+    if let Some(each) = attrs.each.take() {
+        return new(&element_type(ty)?, *each).map(Some);
+    }
+    if attrs.keys.is_none() && attrs.values.is_none() {
+        return Ok(None);
+    }
+    let Some((k, v)) = map_types(ty) else {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "`keys` and `values` need a `HashMap` or a `BTreeMap`",
+        ));
+    };
+    let keys = new(
+        &k,
+        attrs
+            .keys
+            .take()
+            .map_or_else(Default::default, |keys| *keys),
+    )?;
+    let values = new(
+        &v,
+        attrs
+            .values
+            .take()
+            .map_or_else(Default::default, |values| *values),
+    )?;
+    pair(&k, keys, &v, values).map(Some)
+}
+
+/// Key-value pairs: generate both, then shrink the key while holding the value fixed and vice versa.
+fn pair(k: &syn::Type, keys: Strategy, v: &syn::Type, values: Strategy) -> syn::Result<Strategy> {
+    let key = call(&keys.arbitrary, &quote! { g })?;
+    let value = call(&values.arbitrary, &quote! { g })?;
+    let shrink_keys = keys.shrink.map_or_else(
+        || Ok(quote! { ::core::iter::empty() }),
+        |shrink| {
+            let shrunk = call(&shrink, &quote! { &key })?;
+            Ok::<_, syn::Error>(quote! { #shrunk.map(move |key| (key, value.clone())) })
+        },
+    )?;
+    let shrink_values = values.shrink.map_or_else(
+        || Ok(quote! { ::core::iter::empty() }),
+        |shrink| {
+            let shrunk = call(&shrink, &quote! { &value })?;
+            Ok::<_, syn::Error>(quote! { #shrunk.map(move |value| (key.clone(), value)) })
+        },
+    )?;
+    Ok(Strategy {
+        arbitrary: syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> (#k, #v) { (#key, #value) }
+        })?,
+        shrink: Some(syn::parse2(quote! {
+            |entry: &(#k, #v)| -> Box<dyn Iterator<Item = (#k, #v)>> {
+                let (key, value) = entry.clone();
+                let keys = {
+                    let value = value.clone();
+                    #shrink_keys
+                };
+                let values = {
+                    let key = key.clone();
+                    #shrink_values
+                };
+                Box::new(keys.chain(values))
+            }
+        })?),
+    })
+}
+
+/// `None` a quarter of the time (like `quickcheck` itself), otherwise `Some` of the inner strategy.
+/// Shrinks to `None`, then shrinks what's inside.
+fn option(ty: &syn::Type, inner: Strategy) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let some = call(&inner.arbitrary, &quote! { g })?;
+    let shrink_some = inner.shrink.map_or_else(
+        || Ok(quote! { ::quickcheck::single_shrinker(None) }),
+        |shrink| {
+            let shrunk = call(&shrink, &quote! { inner })?;
+            Ok::<_, syn::Error>(
+                quote! { Box::new(::core::iter::once(None).chain(#shrunk.map(Some))) },
+            )
+        },
+    )?;
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                if <u8 as ::quickcheck::Arbitrary>::arbitrary(g).wrapping_rem(4) == 0 {
+                    None
+                } else {
+                    Some(#some)
+                }
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                match *x {
+                    None => ::quickcheck::empty_shrinker(),
+                    Some(ref inner) => #shrink_some,
+                }
+            }
+        })?,
+    ))
+}
+
+/// Collections whose length stays within `min..=max`, even while shrinking.
+/// Sets and maps drop duplicates, so we top them up after collecting, giving up eventually in case there aren't enough distinct elements.
+/// If that leaves fewer than `min`, we panic rather than break the length guarantee.
+/// With an element strategy, shrinking removes chunks of elements (never below `min`), then shrinks one element at a time with that strategy.
+/// With `split` set, elements split `g.size()` between them instead of each drawing from `g` (see `split_collection`),
+/// and a recursive collection (`split` of `Some(true)`) never gets longer than `g.size()`, so it's empty once the size runs out.
+fn collection(
+    ty: &syn::Type,
+    min: &proc_macro2::TokenStream,
+    max: Option<&proc_macro2::TokenStream>,
+    elem: Option<Strategy>,
+    split: Option<bool>,
+) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let elem_ty = element_type(ty)?;
+    let (len, to_vec) = if is_string(ty) {
+        (quote! { chars().count() }, quote! { x.chars().collect() })
+    } else {
+        (quote! { len() }, quote! { x.clone().into_iter().collect() })
+    };
+    let upper = upper_len(&elem_ty, max, split);
+    let (elem_arbitrary, elem_shrink) = match elem {
+        None => (
+            syn::parse2(quote! { <#elem_ty as ::quickcheck::Arbitrary>::arbitrary })?,
+            None,
+        ),
+        Some(strategy) => (strategy.arbitrary, Some(strategy.shrink)),
+    };
+    let one = one_element(&elem_arbitrary, split)?;
+    let shrink = match elem_shrink {
+        None => quote! {
+            Box::new(<#ty as ::quickcheck::Arbitrary>::shrink(x).filter(move |c| c.#len >= min))
+        },
+        Some(shrink_elem) => {
+            let shrink_elems = shrink_elem.map_or_else(
+                || Ok(quote! { ::core::iter::empty() }),
+                |f| {
+                    let candidates = call(&f, &quote! { &items[i] })?;
+                    Ok::<_, syn::Error>(quote! {
+                        (0..n).flat_map(move |i| {
+                            let items = items.clone();
+                            #candidates.map(move |item| {
+                                let mut shrunk = items.clone();
+                                shrunk[i] = item;
+                                shrunk
+                            })
+                        })
+                    })
+                },
+            )?;
+            quote! {
+                let items: Vec<#elem_ty> = #to_vec;
+                let n = items.len();
+                let removals = {
+                    let items = items.clone();
+                    ::core::iter::successors(Some(n.saturating_sub(min)), |k| Some(k >> 1))
+                        .take_while(|k| *k > 0)
+                        .flat_map(move |k| {
+                            let items = items.clone();
+                            (0..=n - k).step_by(k).map(move |start| {
+                                let mut shrunk = items.clone();
+                                shrunk.drain(start..start + k);
+                                shrunk
+                            })
+                        })
+                };
+                Box::new(
+                    removals
+                        .chain(#shrink_elems)
+                        .map(|shrunk| shrunk.into_iter().collect::<#ty>())
+                        .filter(move |c| c.#len >= min),
+                )
+            }
+        }
+    };
+    Ok((
+        syn::parse2(quote! {
+            |g: &mut ::quickcheck::Gen| -> #ty {
+                let min: usize = #min;
+                let max: usize = #upper;
+                assert!(min <= max, "`derive-quickcheck`: empty length range");
+                let extra = <usize as ::quickcheck::Arbitrary>::arbitrary(g);
+                let len = min + (max - min).checked_add(1).map_or(extra, |n| extra.wrapping_rem(n));
+                let mut out: #ty = ::core::iter::repeat_with(|| #one).take(len).collect();
+                for _ in 0..len.saturating_mul(10) {
+                    if out.#len >= len {
+                        break;
+                    }
+                    out.extend(::core::iter::once(#one));
+                }
+                assert!(
+                    out.#len >= min,
+                    "`derive-quickcheck`: couldn't generate {min} distinct elements; does the element type have that many values?",
+                );
+                out
+            }
+        })?,
+        syn::parse2(quote! {
+            |x: &#ty| -> Box<dyn Iterator<Item = #ty>> {
+                let min: usize = #min;
+                #shrink
+            }
+        })?,
+    ))
+}
+
+/// Code for the longest a collection can get: `max` if given, and for a `split` collection, no longer than its size allows.
+fn upper_len(
+    elem_ty: &syn::Type,
+    max: Option<&proc_macro2::TokenStream>,
+    split: Option<bool>,
+) -> proc_macro2::TokenStream {
+    let room = if is_string(elem_ty) || is_collection(elem_ty) {
+        quote! { g.size() >> 1_u32 }
+    } else {
+        quote! { g.size().saturating_sub(1) }
+    };
+    match (max, split) {
+        (Some(n), None) => n.clone(),
+        (None, None) => quote! { ::core::cmp::max(min, g.size()) },
+        (Some(n), Some(true)) => quote! { ::core::cmp::min(#n, g.size()) },
+        (None, Some(true)) => quote! { g.size() },
+        // Like `quickcheck`'s own collections, non-recursive ones stay shorter than their size,
+        // and if their elements have sizes too, there's at least one unit for each (see `one_element`)
+        (Some(n), Some(false)) => quote! { ::core::cmp::min(#n, ::core::cmp::max(min, #room)) },
+        (None, Some(false)) => quote! { ::core::cmp::max(min, #room) },
+    }
+}
+
+/// Code to generate one element of a collection: from `g`, or with `split`, from a `Gen` of its own
+/// with an even share of what's left of `g.size()` after `len` (one unit per element).
+fn one_element(
+    elem_arbitrary: &syn::Expr,
+    split: Option<bool>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let Some(recursive) = split else {
+        let element = call(elem_arbitrary, &quote! { g })?;
+        return Ok(quote! { #element });
+    };
+    let share = quote! { g.size().saturating_sub(len).checked_div(len).unwrap_or_default() };
+    // Elements that aren't recursive get a size of at least 1, since `quickcheck` panics generating collections at size 0
+    let each = if recursive {
+        share
+    } else {
+        quote! { ::core::cmp::max(#share, 1) }
+    };
+    let seed = crate::seed(&quote! { g });
+    let element = call(elem_arbitrary, &quote! { &mut element_gen })?;
+    Ok(quote! {{
+        let mut element_gen = ::quickcheck::Gen::from_size_and_seed(#each, #seed);
+        #element
+    }})
+}